@@ -0,0 +1,76 @@
+// Cost of building the view models and of drawing one frame with a large roster:
+// `cargo bench --bench frame`
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use eframe::egui;
+use serde_json::Value;
+
+use genshin_viewer::image_cache::ImageCache;
+use genshin_viewer::settings::Settings;
+use genshin_viewer::state::{AppState, Event};
+use genshin_viewer::view::RosterView;
+use genshin_viewer::{ui, Roster};
+
+const CHARACTERS: usize = 100;
+
+// The example builds repeated under distinct ids, each with its calculation
+fn large_roster() -> Roster {
+	let builds: Value = serde_json::from_str(include_str!("../example_build.json")).unwrap();
+	let calcs: Value = serde_json::from_str(include_str!("../example_calculation.json")).unwrap();
+	let builds = builds["data"].as_array().unwrap();
+	let calcs = calcs["data"].as_array().unwrap();
+
+	let mut characters = Vec::new();
+	let mut calculations = Vec::new();
+	for i in 0..CHARACTERS {
+		let mut char = builds[i % builds.len()].clone();
+		let id = char["characterId"].as_i64().unwrap();
+		let new_id = id * 1000 + i as i64;
+		if let Some(calc) = calcs.iter().find(|calc| calc["characterId"] == id) {
+			let mut calc = calc.clone();
+			calc["characterId"] = new_id.into();
+			calculations.push(calc);
+		}
+		char["characterId"] = new_id.into();
+		char["name"] = format!("{} {}", char["name"].as_str().unwrap(), i).into();
+		characters.push(char);
+	}
+	Roster {
+		characters,
+		calculations: Value::Array(calculations),
+	}
+}
+
+fn frames(c: &mut Criterion) {
+	let roster = large_roster();
+	c.bench_function("roster_view", |b| b.iter(|| RosterView::new(&roster)));
+
+	let (mut state, _) = AppState::start(None, Settings::default());
+	state.handle(Event::Opened {
+		files: vec![PathBuf::from("bench.json")],
+		result: Ok(roster),
+	});
+	let assets = ui::Assets::new(ImageCache::offline());
+	let ctx = egui::Context::default();
+	let input = egui::RawInput {
+		screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1920.0, 1080.0))),
+		pixels_per_point: Some(1.0),
+		..Default::default()
+	};
+	let frame = |state: &AppState| {
+		ctx.run(input.clone(), |ctx| {
+			ui::show(ctx, state, &assets);
+		})
+	};
+
+	frame(&state);
+	c.bench_function("frame_overview", |b| b.iter(|| frame(&state)));
+
+	state.handle(Event::Select(Some(0)));
+	frame(&state);
+	c.bench_function("frame_character", |b| b.iter(|| frame(&state)));
+}
+
+criterion_group!(benches, frames);
+criterion_main!(benches);
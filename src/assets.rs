@@ -0,0 +1,241 @@
+// Enka asset names for a character. Most follow from the icon, "UI_AvatarIcon_Furina"
+// has "Skill_S_Furina_01" and "UI_Talent_S_Furina_01", the table covers the rest
+use serde_json::Value;
+
+use crate::utils;
+
+const TRAVELER_BOY: i64 = 10000005;
+const TRAVELER_GIRL: i64 = 10000007;
+const ALOY: i64 = 10000062;
+
+struct Override {
+	character_id: i64,
+	// Any skill depot when `None`
+	skill_depot_id: Option<i64>,
+	// The name in the talent and constellation assets, `None` when there are none
+	talents: Option<&'static str>,
+	constellations: bool,
+}
+
+const fn traveler(character_id: i64, skill_depot_id: i64, element: &'static str) -> Override {
+	Override {
+		character_id,
+		skill_depot_id: Some(skill_depot_id),
+		talents: Some(element),
+		constellations: true,
+	}
+}
+
+// First match wins. The Traveler's talents and constellations follow the element
+// they resonate with, which only the skill depot tells apart
+const OVERRIDES: &[Override] = &[
+	traveler(TRAVELER_BOY, 502, "PlayerFire"),
+	traveler(TRAVELER_BOY, 503, "PlayerWater"),
+	traveler(TRAVELER_BOY, 504, "PlayerWind"),
+	traveler(TRAVELER_BOY, 506, "PlayerRock"),
+	traveler(TRAVELER_BOY, 507, "PlayerElectric"),
+	traveler(TRAVELER_BOY, 508, "PlayerGrass"),
+	traveler(TRAVELER_GIRL, 702, "PlayerFire"),
+	traveler(TRAVELER_GIRL, 703, "PlayerWater"),
+	traveler(TRAVELER_GIRL, 704, "PlayerWind"),
+	traveler(TRAVELER_GIRL, 706, "PlayerRock"),
+	traveler(TRAVELER_GIRL, 707, "PlayerElectric"),
+	traveler(TRAVELER_GIRL, 708, "PlayerGrass"),
+	// Before an element is picked, or one we do not know yet
+	Override { character_id: TRAVELER_BOY, skill_depot_id: None, talents: None, constellations: false },
+	Override { character_id: TRAVELER_GIRL, skill_depot_id: None, talents: None, constellations: false },
+	// A guest character, constellations stay locked
+	Override { character_id: ALOY, skill_depot_id: None, talents: Some("Aloy"), constellations: false },
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharacterAssets {
+	// "Furina", also for characters wearing an outfit
+	pub name: String,
+	// The equipped outfit's art when we know it, the default splash otherwise
+	pub splash_url: String,
+	// For builds without talent icons of their own
+	pub skill_icon: Option<String>,
+	pub burst_icon: Option<String>,
+	// C1 to C6, empty for characters without constellation art
+	pub constellation_urls: Vec<String>,
+}
+
+impl CharacterAssets {
+	pub fn resolve(char: &Value) -> Self {
+		let name = base_name(char).to_string();
+		let depot = char["skillDepotId"].as_i64();
+		let found = char["characterId"].as_i64().and_then(|id| {
+			OVERRIDES
+				.iter()
+				.find(|entry| entry.character_id == id && entry.skill_depot_id.is_none_or(|d| Some(d) == depot))
+		});
+		let (talents, constellations) = match found {
+			Some(entry) => (entry.talents, entry.constellations),
+			None => (Some(name.as_str()), true),
+		};
+
+		let splash_url = match costume_id(char).and_then(costume_art) {
+			Some(art) => utils::icon_url(art),
+			None => utils::icon_url(&format!("UI_Gacha_AvatarImg_{}", name)),
+		};
+		let constellation_urls = match talents {
+			Some(talents) if constellations => (1..=6)
+				.map(|i| utils::icon_url(&format!("UI_Talent_S_{}_{:02}", talents, i)))
+				.collect(),
+			_ => Vec::new(),
+		};
+
+		Self {
+			skill_icon: talents.map(|talents| format!("Skill_S_{}_01", talents)),
+			burst_icon: talents.map(|talents| format!("Skill_E_{}_01", talents)),
+			constellation_urls,
+			splash_url,
+			name,
+		}
+	}
+}
+
+// "UI_AvatarIcon_XianglingCostumeWinter" -> "Xiangling", outfits come with an icon
+// of their own but share everything else
+fn base_name(char: &Value) -> &str {
+	char["icon"]
+		.as_str()
+		.and_then(|icon| icon.rsplit('_').next())
+		.and_then(|part| part.split('.').next())
+		.and_then(|name| name.split("Costume").next())
+		.unwrap_or("")
+}
+
+// `costumeId` is an empty string when no outfit is equipped, a number otherwise
+pub fn costume_id(char: &Value) -> Option<i64> {
+	let id = &char["costumeId"];
+	id.as_i64()
+		.or_else(|| id.as_str().and_then(|s| s.parse().ok()))
+		.filter(|id| *id > 0)
+}
+
+// Splash art for outfits. Unknown ids fall back to the default gacha splash.
+pub fn costume_art(costume_id: i64) -> Option<&'static str> {
+	let art = match costume_id {
+		200201 => "UI_Costume_AyakaCostumeFruhling",
+		200301 => "UI_Costume_JeanCostumeSea",
+		200302 => "UI_Costume_JeanCostumeWic",
+		200601 => "UI_Costume_LisaCostumeStudentin",
+		201401 => "UI_Costume_BarbaraCostumeSummertime",
+		201501 => "UI_Costume_KaeyaCostumeDancer",
+		201601 => "UI_Costume_DilucCostumeFlamme",
+		202101 => "UI_Costume_AmborCostumeWic",
+		202301 => "UI_Costume_XianglingCostumeWinter",
+		202501 => "UI_Costume_XingqiuCostumeBamboo",
+		202701 => "UI_Costume_NingguangCostumeFloral",
+		202901 => "UI_Costume_KleeCostumeWitch",
+		203101 => "UI_Costume_FischlCostumeHighness",
+		203701 => "UI_Costume_GanyuCostumeFloral",
+		204101 => "UI_Costume_MonaCostumeWic",
+		204201 => "UI_Costume_KeqingCostumeFeather",
+		204501 => "UI_Costume_RosariaCostumeWic",
+		206101 => "UI_Costume_KiraraCostumeBamboo",
+		206301 => "UI_Costume_ShenheCostumeDai",
+		207001 => "UI_Costume_NilouCostumeFairy",
+		_ => return None,
+	};
+	Some(art)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::fixture_roster;
+	use serde_json::json;
+
+	fn fixture(name: &str) -> Value {
+		fixture_roster().characters.into_iter().find(|c| c["name"] == name).unwrap()
+	}
+
+	fn traveler_build(character_id: i64, skill_depot_id: i64, icon: &str) -> Value {
+		json!({
+			"characterId": character_id,
+			"skillDepotId": skill_depot_id,
+			"icon": format!("https://enka.network/ui/UI_AvatarIcon_{}.png", icon),
+			"costumeId": "",
+		})
+	}
+
+	#[test]
+	fn derives_names_from_the_icon() {
+		let assets = CharacterAssets::resolve(&fixture("Raiden Shogun"));
+		assert_eq!(assets.name, "Shougun");
+		assert_eq!(assets.splash_url, "https://enka.network/ui/UI_Gacha_AvatarImg_Shougun.png");
+		assert_eq!(assets.skill_icon.as_deref(), Some("Skill_S_Shougun_01"));
+		assert_eq!(assets.burst_icon.as_deref(), Some("Skill_E_Shougun_01"));
+		assert_eq!(assets.constellation_urls.len(), 6);
+		assert_eq!(assets.constellation_urls[5], "https://enka.network/ui/UI_Talent_S_Shougun_06.png");
+	}
+
+	#[test]
+	fn outfits_keep_the_character_assets() {
+		let assets = CharacterAssets::resolve(&fixture("Xiangling"));
+		assert_eq!(assets.name, "Xiangling");
+		assert_eq!(assets.splash_url, "https://enka.network/ui/UI_Costume_XianglingCostumeWinter.png");
+		assert_eq!(assets.skill_icon.as_deref(), Some("Skill_S_Xiangling_01"));
+		assert_eq!(assets.constellation_urls[0], "https://enka.network/ui/UI_Talent_S_Xiangling_01.png");
+
+		// An outfit without known art still gets the default splash
+		let mut unknown = fixture("Xiangling");
+		unknown["costumeId"] = 202399.into();
+		assert_eq!(
+			CharacterAssets::resolve(&unknown).splash_url,
+			"https://enka.network/ui/UI_Gacha_AvatarImg_Xiangling.png"
+		);
+	}
+
+	#[test]
+	fn travelers_follow_their_element() {
+		for (id, icon, depots) in [
+			(TRAVELER_BOY, "PlayerBoy", [502, 503, 504, 506, 507, 508]),
+			(TRAVELER_GIRL, "PlayerGirl", [702, 703, 704, 706, 707, 708]),
+		] {
+			let elements = ["PlayerFire", "PlayerWater", "PlayerWind", "PlayerRock", "PlayerElectric", "PlayerGrass"];
+			for (depot, element) in depots.into_iter().zip(elements) {
+				let assets = CharacterAssets::resolve(&traveler_build(id, depot, icon));
+				assert_eq!(assets.name, icon);
+				assert_eq!(assets.splash_url, format!("https://enka.network/ui/UI_Gacha_AvatarImg_{}.png", icon));
+				assert_eq!(assets.skill_icon, Some(format!("Skill_S_{}_01", element)));
+				assert_eq!(assets.burst_icon, Some(format!("Skill_E_{}_01", element)));
+				assert_eq!(
+					assets.constellation_urls[2],
+					format!("https://enka.network/ui/UI_Talent_S_{}_03.png", element)
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn travelers_without_an_element_have_no_talent_art() {
+		for build in [traveler_build(TRAVELER_BOY, 501, "PlayerBoy"), traveler_build(TRAVELER_GIRL, 799, "PlayerGirl")] {
+			let assets = CharacterAssets::resolve(&build);
+			assert_eq!(assets.skill_icon, None);
+			assert_eq!(assets.burst_icon, None);
+			assert!(assets.constellation_urls.is_empty());
+		}
+	}
+
+	#[test]
+	fn aloy_has_talents_but_no_constellations() {
+		let aloy = json!({ "characterId": ALOY, "skillDepotId": 6201, "icon": "UI_AvatarIcon_Aloy" });
+		let assets = CharacterAssets::resolve(&aloy);
+		assert_eq!(assets.skill_icon.as_deref(), Some("Skill_S_Aloy_01"));
+		assert!(assets.constellation_urls.is_empty());
+	}
+
+	#[test]
+	fn reads_costume_ids() {
+		assert_eq!(costume_id(&json!({ "costumeId": "" })), None);
+		assert_eq!(costume_id(&json!({ "costumeId": 0 })), None);
+		assert_eq!(costume_id(&json!({ "costumeId": 202301 })), Some(202301));
+		assert_eq!(costume_id(&json!({ "costumeId": "202301" })), Some(202301));
+		assert_eq!(costume_art(202301), Some("UI_Costume_XianglingCostumeWinter"));
+		assert_eq!(costume_art(1), None);
+	}
+}
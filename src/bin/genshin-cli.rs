@@ -0,0 +1,227 @@
+// Terminal version of the viewer, prints every build of an account
+use colored::Colorize;
+use genshin_viewer::overview::OwnerProfile;
+use genshin_viewer::stats::{self, StatKind};
+use genshin_viewer::view::{CharacterView, RosterView};
+use genshin_viewer::{format_compact, format_number, theme, uid, utils, ApiClient, NumberLocale, Roster};
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: genshin-cli [--api-url URL] [UID]\n       genshin-cli --from-file FILE [--from-file FILE]";
+
+struct Args {
+	api_url: String,
+	uid: Option<String>,
+	// Saved JSON, read instead of fetching
+	files: Vec<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = Args {
+		api_url: utils::BASE_URL.to_string(),
+		uid: None,
+		files: Vec::new(),
+	};
+	let mut argv = std::env::args().skip(1);
+	while let Some(arg) = argv.next() {
+		match arg.as_str() {
+			"--api-url" => args.api_url = argv.next().ok_or("--api-url needs a value")?,
+			"--from-file" => args.files.push(argv.next().ok_or("--from-file needs a value")?.into()),
+			"-h" | "--help" => return Err(USAGE.to_string()),
+			_ if arg.starts_with('-') => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+			_ if args.uid.is_none() => args.uid = Some(arg),
+			_ => return Err(USAGE.to_string()),
+		}
+	}
+	if !args.files.is_empty() && args.uid.is_some() {
+		return Err(format!("Give a UID or --from-file, not both\n{}", USAGE));
+	}
+	Ok(args)
+}
+
+fn prompt_uid() -> String {
+	print!("Enter Genshin Impact UID: ");
+	io::stdout().flush().ok();
+	let mut line = String::new();
+	io::stdin().lock().read_line(&mut line).ok();
+	line.trim().to_string()
+}
+
+fn colorize(text: &str, (r, g, b): (u8, u8, u8)) -> String {
+	text.truecolor(r, g, b).to_string()
+}
+
+// Talents and stats, which only a real build has
+fn print_build(char: &Value) {
+	println!("\nTalents:");
+	for (label, key) in [
+		("Normal Attack", "normalAttacks"),
+		("Elemental Skill", "elementalSkill"),
+		("Elemental Burst", "elementalBurst"),
+	] {
+		println!("  {}: {}", label, char["talentsLevelMap"][key]["level"].as_i64().unwrap_or(0));
+	}
+
+	println!("\nStats:");
+	for kind in stats::CORE_STATS {
+		let value = stats::character_stat(char, *kind).unwrap_or(0.0);
+		println!("  {}: {}", kind.name(), kind.format(value));
+	}
+	for kind in stats::BONUS_STATS {
+		if let Some(value) = stats::character_stat(char, *kind).filter(|value| *value > 0.0) {
+			println!("  {}: {}", kind.name(), kind.format(value));
+		}
+	}
+}
+
+fn print_character(char: &Value, view: &CharacterView, roster: &Roster) {
+	println!("\n{}", "=".repeat(50));
+
+	let element = char["characterMetadata"]["element"].as_str().unwrap_or("");
+	println!(
+		"{} (C{})",
+		colorize(char["name"].as_str().unwrap_or("Unknown"), theme::element_rgb(element)),
+		char["constellation"].as_i64().unwrap_or(0)
+	);
+	if view.variant != "current" {
+		println!("Saved build: {}", view.variant_name());
+	}
+	if let Some(level) = utils::LevelInfo::character(char) {
+		println!("Level {}/{}", level.level, level.cap);
+	}
+
+	if view.calculation_only {
+		println!("Only on the leaderboard, full stats are unavailable");
+	} else {
+		print_build(char);
+	}
+
+	let weapon = &char["weapon"];
+	println!(
+		"\nWeapon: {} R{}",
+		colorize(weapon["name"].as_str().unwrap_or(""), (255, 215, 0)),
+		weapon["weaponInfo"]["refinementLevel"]["value"].as_i64().unwrap_or(0).saturating_add(1)
+	);
+	if let Some(level) = utils::LevelInfo::weapon(weapon) {
+		println!("  Level {}/{}", level.level, level.cap);
+	}
+	for (kind, value) in roster.weapon_stats(char) {
+		println!("  {}: {}", kind.name(), kind.format(value));
+	}
+
+	println!("\nArtifact Sets:");
+	if let Some(sets) = char["artifactSets"].as_object() {
+		for (name, details) in sets {
+			println!("  {} ({}pc)", name, details["count"].as_i64().unwrap_or(0));
+		}
+	}
+
+	println!("\nArtifact Main Stats:");
+	if let Some(pieces) = char["artifactObjects"].as_object() {
+		for (piece, details) in pieces {
+			let key = details["mainStatKey"].as_str().unwrap_or("");
+			let stat = StatKind::from_key(key).map(|kind| kind.name()).unwrap_or(key);
+			println!("  {}: {}", utils::artifact_slot_name(piece), stat);
+		}
+	}
+
+	println!("\nBuild Quality:");
+	println!("  Crit Value: {:.2}", char["critValue"].as_f64().unwrap_or(0.0));
+
+	let fit = roster.calculation_for(char).map(|calc| &calc["calculations"]["fit"]);
+	if let Some(fit) = fit.filter(|fit| fit.is_object()) {
+		println!("\nBuild Analysis:");
+		println!("  {}", fit["name"].as_str().unwrap_or(""));
+		if let Some(details) = fit["details"].as_str() {
+			println!("  Details: {}", details);
+		}
+		if let Some(result) = fit["result"].as_f64() {
+			println!("  Result: {} ({})", format_compact(result, NumberLocale::default()), format_number(result));
+		}
+		if let (Some(ranking), Some(out_of)) = (fit["ranking"].as_i64(), fit["outOf"].as_i64()) {
+			println!(
+				"  Ranking: {} out of {} (Top {:.2}%)",
+				ranking,
+				out_of,
+				ranking as f64 / out_of.max(1) as f64 * 100.0
+			);
+		}
+	}
+}
+
+async fn fetch(args: &Args) -> Result<Roster, ExitCode> {
+	let uid = args.uid.clone().unwrap_or_else(prompt_uid);
+	if let Err(e) = uid::validate(&uid) {
+		eprintln!("Invalid UID {:?}: {}", uid, e);
+		return Err(ExitCode::from(2));
+	}
+	let uid = uid.trim();
+
+	println!("Fetching data for UID {}...", uid);
+	let client = ApiClient::new(&args.api_url);
+	match Roster::fetch(&client, uid).await {
+		Ok((roster, warnings)) if !roster.characters.is_empty() => {
+			for warning in warnings {
+				eprintln!("Warning: {}", warning);
+			}
+			Ok(roster)
+		}
+		Ok(_) => {
+			eprintln!("No data found for this UID");
+			Err(ExitCode::FAILURE)
+		}
+		Err(e) => {
+			eprintln!("Error fetching user data: {}", e);
+			Err(ExitCode::FAILURE)
+		}
+	}
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+	let args = match parse_args() {
+		Ok(args) => args,
+		Err(message) => {
+			eprintln!("{}", message);
+			return ExitCode::from(2);
+		}
+	};
+
+	let roster = if args.files.is_empty() {
+		match fetch(&args).await {
+			Ok(roster) => roster,
+			Err(code) => return code,
+		}
+	} else {
+		match Roster::open(&args.files) {
+			Ok(roster) => roster,
+			Err(e) => {
+				eprintln!("Error reading saved data: {}", e);
+				return ExitCode::FAILURE;
+			}
+		}
+	};
+
+	if let Some(owner) = OwnerProfile::from_builds(&roster.characters) {
+		println!("\nOwner Info:");
+		println!("  {} (AR{})", owner.nickname, owner.adventure_rank);
+		println!("  Server: {}", owner.region);
+	}
+
+	// Characters with only leaderboard results are listed too, saved builds follow
+	// the current one
+	let view = RosterView::new(&roster);
+	let characters: Vec<usize> = (0..view.characters.len()).filter(|index| view.primary(*index) == *index).collect();
+	println!("\nFound {} characters", characters.len());
+	for index in characters {
+		for variant in &view.characters[index].variants {
+			if let Some(build) = view.build(&roster, *variant) {
+				print_character(build, &view.characters[*variant], &roster);
+			}
+		}
+	}
+
+	ExitCode::SUCCESS
+}
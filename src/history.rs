@@ -0,0 +1,116 @@
+use serde_json::Value;
+
+use crate::models::{self, Roster};
+use crate::utils;
+
+// One stored response, `time` is seconds since the epoch
+pub struct Snapshot {
+	pub time: u64,
+	pub roster: Roster,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+	pub time: u64,
+	pub level: Option<i64>,
+	pub constellation: i64,
+	pub crit_value: f64,
+	// (ranking, out of)
+	pub ranking: Option<(i64, i64)>,
+}
+
+impl HistoryEntry {
+	fn from_build(time: u64, roster: &Roster, char: &Value) -> Self {
+		let fit = roster.calculation_for(char).map(|calc| &calc["calculations"]["fit"]);
+		Self {
+			time,
+			level: utils::LevelInfo::character(char).map(|level| level.level),
+			constellation: char["constellation"].as_i64().unwrap_or(0),
+			crit_value: char["critValue"].as_f64().unwrap_or(0.0),
+			ranking: fit.and_then(|fit| Some((fit["ranking"].as_i64()?, fit["outOf"].as_i64()?))),
+		}
+	}
+
+	fn same_build(&self, other: &Self) -> bool {
+		self.level == other.level
+			&& self.constellation == other.constellation
+			&& self.crit_value == other.crit_value
+			&& self.ranking == other.ranking
+	}
+}
+
+// Newest first, only the snapshots where something about the build changed. Each
+// saved variant has its own history
+pub fn character_history(snapshots: &[Snapshot], character_id: i64, build_type: &str) -> Vec<HistoryEntry> {
+	let mut entries: Vec<HistoryEntry> = Vec::new();
+	for snapshot in snapshots {
+		let roster = &snapshot.roster;
+		let Some(char) = roster.characters.iter().find(|c| c["characterId"].as_i64() == Some(character_id) && models::build_type(c) == build_type)
+		else {
+			continue;
+		};
+		let entry = HistoryEntry::from_build(snapshot.time, roster, char);
+		if entries.last().map(|last| last.same_build(&entry)) != Some(true) {
+			entries.push(entry);
+		}
+	}
+	entries.reverse();
+	entries
+}
+
+// "2024-05-01 12:30" in UTC
+pub fn format_time(secs: u64) -> String {
+	let days = (secs / 86400) as i64;
+	let minutes = secs % 86400 / 60;
+
+	// Days to a civil date, from Howard Hinnant's date algorithms
+	let z = days + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z - era * 146097;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + (month <= 2) as i64;
+
+	format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::fixture_roster;
+
+	#[test]
+	fn formats_utc_times() {
+		assert_eq!(format_time(0), "1970-01-01 00:00");
+		assert_eq!(format_time(951_782_400), "2000-02-29 00:00");
+		assert_eq!(format_time(1_714_566_600), "2024-05-01 12:30");
+	}
+
+	#[test]
+	fn keeps_only_changes() {
+		let furina_id = |roster: &Roster| {
+			roster.characters.iter().find(|c| c["name"] == "Furina").unwrap()["characterId"].as_i64().unwrap()
+		};
+		let roster = fixture_roster();
+		let id = furina_id(&roster);
+
+		let mut upgraded = fixture_roster();
+		for char in upgraded.characters.iter_mut().filter(|c| c["characterId"] == id) {
+			char["critValue"] = 250.0.into();
+		}
+		let snapshots = vec![
+			Snapshot { time: 1, roster: fixture_roster() },
+			Snapshot { time: 2, roster },
+			Snapshot { time: 3, roster: upgraded },
+		];
+
+		let history = character_history(&snapshots, id, "current");
+		assert_eq!(history.iter().map(|entry| entry.time).collect::<Vec<_>>(), vec![3, 1]);
+		assert_eq!(history[0].crit_value, 250.0);
+		assert!(history[1].ranking.is_some());
+		assert!(character_history(&snapshots, -1, "current").is_empty());
+	}
+}
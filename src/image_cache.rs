@@ -0,0 +1,345 @@
+use eframe::egui;
+use egui_extras::RetainedImage;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::{paths, utils};
+
+pub struct CharacterIcon {
+	pub texture: RetainedImage,
+	pub loading: bool,
+	pub error: bool,
+}
+
+// Images keyed by URL, fetched in the background the first time they are asked for
+#[derive(Clone)]
+pub struct ImageCache {
+	icons: Arc<Mutex<HashMap<String, CharacterIcon>>>,
+	// When each URL was last asked for, the least recently used get evicted first
+	used: Arc<Mutex<HashMap<String, u64>>>,
+	uses: Arc<AtomicU64>,
+	// Bytes of decoded images to keep, and of files on disk. 0 keeps everything
+	limit: Arc<AtomicUsize>,
+	// Without a runtime nothing is fetched and every image stays a loading placeholder
+	runtime: Option<tokio::runtime::Handle>,
+	http: reqwest::Client,
+	// Downloaded files are kept here and read back instead of fetching again
+	disk: Option<PathBuf>,
+}
+
+impl ImageCache {
+	pub fn new(runtime: tokio::runtime::Handle) -> Self {
+		Self {
+			icons: Arc::new(Mutex::new(HashMap::new())),
+			used: Arc::new(Mutex::new(HashMap::new())),
+			uses: Arc::new(AtomicU64::new(0)),
+			limit: Arc::new(AtomicUsize::new(0)),
+			runtime: Some(runtime),
+			http: reqwest::Client::new(),
+			disk: None,
+		}
+	}
+
+	pub fn offline() -> Self {
+		Self {
+			icons: Arc::new(Mutex::new(HashMap::new())),
+			used: Arc::new(Mutex::new(HashMap::new())),
+			uses: Arc::new(AtomicU64::new(0)),
+			limit: Arc::new(AtomicUsize::new(0)),
+			runtime: None,
+			http: reqwest::Client::new(),
+			disk: None,
+		}
+	}
+
+	pub fn with_disk_cache(mut self, dir: PathBuf) -> Self {
+		self.disk = Some(dir);
+		self
+	}
+
+	pub fn lock(&self) -> MutexGuard<'_, HashMap<String, CharacterIcon>> {
+		self.icons.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	pub fn set_limit(&self, bytes: usize) {
+		self.limit.store(bytes, Ordering::Relaxed);
+		self.trim();
+		if let (Some(runtime), Some(dir)) = (&self.runtime, self.disk.clone()) {
+			runtime.spawn_blocking(move || prune_dir(&dir, bytes));
+		}
+	}
+
+	fn trim(&self) {
+		let limit = self.limit.load(Ordering::Relaxed);
+		if limit == 0 {
+			return;
+		}
+		let size = |icon: &CharacterIcon| {
+			let [width, height] = icon.texture.size();
+			width * height * 4
+		};
+
+		let mut icons = self.lock();
+		let mut used = self.used.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		let mut total: usize = icons.values().map(size).sum();
+		// Images still downloading are left alone, they are about to be shown
+		let mut oldest: Vec<(u64, String)> = icons
+			.iter()
+			.filter(|(_, icon)| !icon.loading)
+			.map(|(url, _)| (used.get(url).copied().unwrap_or(0), url.clone()))
+			.collect();
+		oldest.sort_unstable();
+		for (_, url) in oldest {
+			if total <= limit {
+				break;
+			}
+			if let Some(icon) = icons.remove(&url) {
+				total -= size(&icon);
+			}
+			used.remove(&url);
+		}
+	}
+
+	pub async fn load_icon(http: &reqwest::Client, url: &str) -> Result<RetainedImage, Box<dyn Error>> {
+		let bytes = Self::download(http, url).await?;
+		let image = RetainedImage::from_image_bytes(url, &bytes)?;
+		Ok(image)
+	}
+
+	async fn download(http: &reqwest::Client, url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+		let response = http.get(url).send().await?.error_for_status()?;
+		Ok(response.bytes().await?.to_vec())
+	}
+
+	// Disk first, then the network. Only files that decode are written back.
+	async fn load(&self, url: &str) -> Option<RetainedImage> {
+		let file = self.disk.as_ref().map(|dir| dir.join(paths::cache_key(url)));
+		if let Some(bytes) = file.as_ref().and_then(|file| std::fs::read(file).ok()) {
+			if let Ok(image) = RetainedImage::from_image_bytes(url, &bytes) {
+				// The modified time tells `prune_dir` what was used last
+				if let Some(file) = &file {
+					std::fs::File::options()
+						.append(true)
+						.open(file)
+						.and_then(|file| file.set_modified(SystemTime::now()))
+						.ok();
+				}
+				return Some(image);
+			}
+		}
+
+		let bytes = Self::download(&self.http, url).await.ok()?;
+		let image = RetainedImage::from_image_bytes(url, &bytes).ok()?;
+		if let Some(file) = file {
+			if let Some(dir) = file.parent() {
+				std::fs::create_dir_all(dir).ok();
+				std::fs::write(&file, &bytes).ok();
+				prune_dir(dir, self.limit.load(Ordering::Relaxed));
+			}
+		}
+		Some(image)
+	}
+
+	// Call before every lookup, it also marks the image as used
+	pub fn ensure(&self, icon_name: &str) {
+		let icon_url = utils::icon_url(icon_name);
+
+		let mut icons = self.lock();
+		let use_id = self.uses.fetch_add(1, Ordering::Relaxed) + 1;
+		self.used
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+			.insert(icon_url.clone(), use_id);
+		if icons.contains_key(&icon_url) {
+			return;
+		}
+		icons.insert(
+			icon_url.clone(),
+			CharacterIcon {
+				texture: RetainedImage::from_color_image(
+					"loading",
+					egui::ColorImage::new([32, 32], egui::Color32::GRAY),
+				),
+				loading: true,
+				error: false,
+			},
+		);
+		drop(icons);
+
+		let Some(runtime) = &self.runtime else {
+			return;
+		};
+		let cache = self.clone();
+		runtime.spawn(async move {
+			let result = cache.load(&icon_url).await;
+			let mut icons = cache.lock();
+			if let Some(icon) = icons.get_mut(&icon_url) {
+				match result {
+					Some(image) => icon.texture = image,
+					None => icon.error = true,
+				}
+				icon.loading = false;
+			}
+			drop(icons);
+			cache.trim();
+		});
+	}
+}
+
+// Deletes the least recently modified files until `dir` fits in `limit` bytes. The
+// newest file always stays, even when it is larger on its own.
+fn prune_dir(dir: &Path, limit: usize) {
+	if limit == 0 {
+		return;
+	}
+	let Ok(entries) = std::fs::read_dir(dir) else {
+		return;
+	};
+	let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+		.flatten()
+		.filter_map(|entry| {
+			let metadata = entry.metadata().ok().filter(|metadata| metadata.is_file())?;
+			Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+		})
+		.collect();
+	files.sort_unstable();
+	let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+	files.pop();
+	for (_, len, file) in files {
+		if total <= limit as u64 {
+			break;
+		}
+		if std::fs::remove_file(file).is_ok() {
+			total -= len;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::{temp_dir, MockServer, Reply};
+	use std::time::{Duration, Instant};
+
+	fn wait_until_loaded(cache: &ImageCache, url: &str) {
+		let deadline = Instant::now() + Duration::from_secs(5);
+		while cache.lock().get(url).map(|icon| icon.loading).unwrap_or(true) {
+			assert!(Instant::now() < deadline, "timed out loading {}", url);
+			std::thread::sleep(Duration::from_millis(10));
+		}
+	}
+
+	#[test]
+	fn loads_images_in_the_background() {
+		let server = MockServer::start();
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let cache = ImageCache::new(runtime.handle().clone());
+		let url = server.image_url("hp");
+
+		cache.ensure(&url);
+		wait_until_loaded(&cache, &url);
+
+		let icons = cache.lock();
+		assert!(!icons[&url].error);
+		assert_ne!(icons[&url].texture.size(), [32, 32]);
+	}
+
+	#[test]
+	fn fetches_each_url_once() {
+		let server = MockServer::start();
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let cache = ImageCache::new(runtime.handle().clone());
+		let url = server.image_url("atk");
+
+		cache.ensure(&url);
+		cache.ensure(&url);
+		wait_until_loaded(&cache, &url);
+		cache.ensure(&url);
+
+		assert_eq!(server.hits("/ui/atk.png"), 1);
+	}
+
+	#[test]
+	fn reuses_downloads_from_disk() {
+		let dir = temp_dir("images");
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let server = MockServer::start();
+		let url = server.image_url("def");
+
+		let cache = ImageCache::new(runtime.handle().clone()).with_disk_cache(dir.clone());
+		cache.ensure(&url);
+		wait_until_loaded(&cache, &url);
+		assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+		// A new session with the server gone still gets the image
+		drop(server);
+		let cache = ImageCache::new(runtime.handle().clone()).with_disk_cache(dir);
+		cache.ensure(&url);
+		wait_until_loaded(&cache, &url);
+		assert!(!cache.lock()[&url].error);
+	}
+
+	#[test]
+	fn evicts_least_recently_used_images_over_the_limit() {
+		let server = MockServer::start();
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let cache = ImageCache::new(runtime.handle().clone());
+		let urls: Vec<String> = (0..3).map(|i| format!("{}?copy={}", server.image_url("hp"), i)).collect();
+
+		cache.ensure(&urls[0]);
+		wait_until_loaded(&cache, &urls[0]);
+		let [width, height] = cache.lock()[&urls[0]].texture.size();
+		cache.set_limit(width * height * 4 * 2);
+		cache.ensure(&urls[1]);
+		wait_until_loaded(&cache, &urls[1]);
+
+		// The first image was asked for again, so the second is the one to go
+		cache.ensure(&urls[0]);
+		cache.ensure(&urls[2]);
+		wait_until_loaded(&cache, &urls[2]);
+		let icons = cache.lock();
+		assert!(icons.contains_key(&urls[0]));
+		assert!(!icons.contains_key(&urls[1]));
+		assert!(icons.contains_key(&urls[2]));
+	}
+
+	#[test]
+	fn prunes_the_disk_cache_to_the_limit() {
+		let dir = temp_dir("prune");
+		let now = SystemTime::now();
+		for (i, name) in ["old", "used", "new"].into_iter().enumerate() {
+			let file = dir.join(name);
+			std::fs::write(&file, [0u8; 100]).unwrap();
+			let modified = now - Duration::from_secs(60 * (3 - i as u64));
+			std::fs::File::options().append(true).open(&file).unwrap().set_modified(modified).unwrap();
+		}
+
+		prune_dir(&dir, 0);
+		assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+		prune_dir(&dir, 250);
+		assert!(!dir.join("old").exists());
+		assert!(dir.join("used").exists() && dir.join("new").exists());
+		// The newest file stays even when it alone is over the limit
+		prune_dir(&dir, 50);
+		assert!(!dir.join("used").exists());
+		assert!(dir.join("new").exists());
+	}
+
+	#[test]
+	fn failed_downloads_are_marked() {
+		let server = MockServer::start();
+		server.reply("/ui/broken.png", Reply::Bytes("image/png", b"not a png".to_vec()));
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let cache = ImageCache::new(runtime.handle().clone());
+
+		for url in [server.image_url("missing"), server.image_url("broken")] {
+			cache.ensure(&url);
+			wait_until_loaded(&cache, &url);
+			assert!(cache.lock()[&url].error, "{} should fail", url);
+		}
+	}
+}
@@ -0,0 +1,285 @@
+use serde_json::Value;
+
+// Fields the viewer reads from a build, "*" stands for any key or index. A field is
+// consumed when it lies on one of these paths or inside one. A test checks the view
+// models against this list, the renderers have to be kept in step by hand.
+pub const BUILD_FIELDS: &[&str] = &[
+	"artifactObjects.*.mainStatKey",
+	"artifactSets.*.count",
+	"artifactSets.*.icon",
+	"characterId",
+	"characterMetadata.element",
+	"constellation",
+	"costumeId",
+	"critValue",
+	"fetterInfo.expLevel",
+	"icon",
+	"name",
+	"nameCardLink",
+	"owner.adventureRank",
+	"owner.nameCard",
+	"owner.nickname",
+	"owner.patreon.active",
+	"owner.profilePicture.assets.icon",
+	"owner.region",
+	"profilePictureLink",
+	"propMap.ascension.val",
+	"propMap.level.val",
+	"skillDepotId",
+	"stats.*.value",
+	"talentsLevelMap.*.boosted",
+	"talentsLevelMap.*.icon",
+	"talentsLevelMap.*.level",
+	"talentsLevelMap.*.rawLevel",
+	"type",
+	"weapon.flat.weaponStats.*.stat",
+	"weapon.flat.weaponStats.*.statValue",
+	"weapon.icon",
+	"weapon.name",
+	"weapon.weaponInfo.level",
+	"weapon.weaponInfo.promoteLevel",
+	"weapon.weaponInfo.refinementLevel.value",
+	"weaponType",
+];
+
+// Calculation-only characters also get a stand-in build from `name`, `icon`,
+// `constellation`, `artifactSets` and the weapon
+pub const CALCULATION_FIELDS: &[&str] = &[
+	"artifactSets.*.count",
+	"artifactSets.*.icon",
+	"calculations.*.details",
+	"calculations.*.name",
+	"calculations.*.outOf",
+	"calculations.*.ranking",
+	"calculations.*.result",
+	"calculations.*.weapon.icon",
+	"calculations.*.weapon.name",
+	"calculations.*.weapon.rarity",
+	"calculations.*.weapon.refinement",
+	"calculations.*.weapon.substat",
+	"calculations.*.weapon.type",
+	"characterId",
+	"constellation",
+	"icon",
+	"name",
+	"type",
+	"weapon.flat.weaponStats.*.stat",
+	"weapon.flat.weaponStats.*.statValue",
+];
+
+// Whether the viewer reads `path` or anything under it. Containers on the way to a
+// read field count as consumed, their unread children do not.
+pub fn is_consumed(path: &[String], fields: &[&str]) -> bool {
+	fields.iter().any(|field| {
+		field
+			.split('.')
+			.zip(path)
+			.all(|(expected, key)| expected == "*" || expected == key)
+	})
+}
+
+// A jq style path into `root`: `.weapon.weaponInfo.level`, `.artifactSets["Gilded Dreams"].count`
+pub fn path_text(root: &Value, path: &[String]) -> String {
+	if path.is_empty() {
+		return ".".to_string();
+	}
+	let mut text = String::new();
+	let mut value = root;
+	for key in path {
+		if let Value::Array(items) = value {
+			text.push_str(&format!("[{}]", key));
+			value = key.parse().ok().and_then(|i: usize| items.get(i)).unwrap_or(&Value::Null);
+			continue;
+		}
+		if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+			text.push('.');
+			text.push_str(key);
+		} else {
+			text.push_str(&format!("[{}]", Value::from(key.as_str())));
+		}
+		value = &value[key.as_str()];
+	}
+	text
+}
+
+// What "Copy value" puts on the clipboard, strings without their quotes
+pub fn copy_text(value: &Value) -> String {
+	match value {
+		Value::String(text) => text.clone(),
+		Value::Array(_) | Value::Object(_) => serde_json::to_string_pretty(value).unwrap_or_default(),
+		_ => value.to_string(),
+	}
+}
+
+// Whether the key or any key or scalar below it contains `query`, ignoring case
+pub fn matches(key: &str, value: &Value, query: &str) -> bool {
+	if query.is_empty() {
+		return true;
+	}
+	let query = query.to_lowercase();
+	contains(key, value, &query)
+}
+
+fn contains(key: &str, value: &Value, query: &str) -> bool {
+	if key.to_lowercase().contains(query) {
+		return true;
+	}
+	match value {
+		Value::Object(map) => map.iter().any(|(key, value)| contains(key, value, query)),
+		Value::Array(items) => items.iter().enumerate().any(|(i, value)| contains(&i.to_string(), value, query)),
+		_ => copy_text(value).to_lowercase().contains(query),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::assets::CharacterAssets;
+	use crate::models::Roster;
+	use crate::test_support::fixture_roster;
+	use crate::view::RosterView;
+	use serde_json::json;
+
+	fn path(keys: &[&str]) -> Vec<String> {
+		keys.iter().map(|key| key.to_string()).collect()
+	}
+
+	#[test]
+	fn marks_unread_fields() {
+		let consumed = |keys: &[&str]| is_consumed(&path(keys), BUILD_FIELDS);
+		assert!(consumed(&["critValue"]));
+		assert!(consumed(&["weapon"]));
+		assert!(consumed(&["weapon", "weaponInfo", "refinementLevel", "value"]));
+		assert!(consumed(&["stats", "atk", "value"]));
+		assert!(consumed(&["weapon", "flat", "weaponStats", "1", "statValue"]));
+		assert!(!consumed(&["_id"]));
+		assert!(!consumed(&["md5"]));
+		assert!(!consumed(&["weapon", "weaponId"]));
+		assert!(!consumed(&["stats", "atk", "base"]));
+		// Saved builds are told apart by their type, on both sides
+		assert!(consumed(&["type"]));
+		assert!(is_consumed(&path(&["type"]), CALCULATION_FIELDS));
+
+		// Top level fields the detail view shows
+		let roster = fixture_roster();
+		let char = &roster.characters[0];
+		for key in ["name", "characterId", "constellation", "critValue", "weapon", "artifactSets", "talentsLevelMap"] {
+			assert!(char.get(key).is_some(), "{} is not in the fixture", key);
+			assert!(consumed(&[key]), "{} is read by the viewer", key);
+		}
+		// Everything `CharacterAssets::resolve` looks at
+		for key in ["characterId", "skillDepotId", "icon", "costumeId"] {
+			assert!(char.get(key).is_some(), "{} is not in the fixture", key);
+			assert!(consumed(&[key]), "{} is read by the asset resolver", key);
+		}
+	}
+
+	// Every scalar below `value`, by path
+	fn leaves(value: &Value, path: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+		match value {
+			Value::Object(map) => {
+				for (key, value) in map {
+					path.push(key.clone());
+					leaves(value, path, out);
+					path.pop();
+				}
+			}
+			Value::Array(items) => {
+				for (i, value) in items.iter().enumerate() {
+					path.push(i.to_string());
+					leaves(value, path, out);
+					path.pop();
+				}
+			}
+			_ => out.push(path.clone()),
+		}
+	}
+
+	fn set_null(value: &mut Value, path: &[String]) {
+		let target = path.iter().fold(value, |value, key| match value {
+			Value::Array(items) => &mut items[key.parse::<usize>().unwrap()],
+			value => &mut value[key.as_str()],
+		});
+		*target = Value::Null;
+	}
+
+	// Nulls every field `fields` leaves out, one at a time, and checks `read` does not notice
+	fn assert_reads_only(fields: &[&str], root: &Value, read: impl Fn(&Value) -> String) {
+		let expected = read(root);
+		let mut paths = Vec::new();
+		leaves(root, &mut Vec::new(), &mut paths);
+		let unlisted: Vec<String> = paths
+			.iter()
+			.filter(|path| !is_consumed(path, fields))
+			.filter(|path| {
+				let mut changed = root.clone();
+				set_null(&mut changed, path);
+				read(&changed) != expected
+			})
+			.map(|path| path_text(root, path))
+			.collect();
+		assert!(unlisted.is_empty(), "read but not listed: {}", unlisted.join(", "));
+	}
+
+	#[test]
+	fn lists_every_build_field_the_viewer_reads() {
+		let roster = fixture_roster();
+		for char in &roster.characters {
+			assert_reads_only(BUILD_FIELDS, char, |char| {
+				let roster = Roster {
+					characters: vec![char.clone()],
+					calculations: roster.calculations.clone(),
+				};
+				let view = RosterView::new(&roster);
+				format!(
+					"{:?} {:?} {:?} {:?}",
+					view.characters,
+					view.owner,
+					view.summary,
+					CharacterAssets::resolve(char)
+				)
+			});
+		}
+	}
+
+	#[test]
+	fn lists_every_calculation_field_the_viewer_reads() {
+		// As the only entry, so calculation-only characters get a stand-in from it
+		for calc in fixture_roster().calculations.as_array().unwrap() {
+			assert_reads_only(CALCULATION_FIELDS, calc, |calc| {
+				let roster = Roster {
+					characters: Vec::new(),
+					calculations: Value::Array(vec![calc.clone()]),
+				};
+				let view = RosterView::new(&roster);
+				format!("{:?} {:?} {:?}", view.characters, view.build(&roster, 0), view.summary)
+			});
+		}
+	}
+
+	#[test]
+	fn paths_read_like_jq() {
+		let build = json!({
+			"artifactSets": { "Gilded Dreams": { "count": 4 } },
+			"weapon": { "flat": { "weaponStats": [{ "stat": "FIGHT_PROP_BASE_ATTACK" }] } },
+		});
+		assert_eq!(path_text(&build, &[]), ".");
+		assert_eq!(path_text(&build, &path(&["artifactSets", "Gilded Dreams", "count"])), ".artifactSets[\"Gilded Dreams\"].count");
+		assert_eq!(path_text(&build, &path(&["weapon", "flat", "weaponStats", "0", "stat"])), ".weapon.flat.weaponStats[0].stat");
+	}
+
+	#[test]
+	fn search_looks_at_keys_and_values() {
+		let weapon = json!({ "name": "Splendor of Tranquil Waters", "weaponInfo": { "level": 90 } });
+		assert!(matches("weapon", &weapon, ""));
+		assert!(matches("weapon", &weapon, "WEAPON"));
+		assert!(matches("weapon", &weapon, "tranquil"));
+		assert!(matches("weapon", &weapon, "level"));
+		assert!(matches("weapon", &weapon, "90"));
+		assert!(!matches("weapon", &weapon, "refinement"));
+
+		assert_eq!(copy_text(&json!("Furina")), "Furina");
+		assert_eq!(copy_text(&json!(90)), "90");
+		assert_eq!(copy_text(&json!([1])), "[\n  1\n]");
+	}
+}
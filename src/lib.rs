@@ -0,0 +1,24 @@
+// Akasha client, data helpers and the stat registry shared by the desktop app and the CLI.
+// The image cache and egui views are behind the `gui` feature.
+pub mod assets;
+pub mod history;
+#[cfg(feature = "gui")]
+pub mod image_cache;
+pub mod inspector;
+pub mod models;
+pub mod overview;
+pub mod paths;
+pub mod settings;
+pub mod state;
+pub mod stats;
+#[cfg(test)]
+mod test_support;
+pub mod theme;
+#[cfg(feature = "gui")]
+pub mod ui;
+pub mod uid;
+pub mod utils;
+pub mod view;
+
+pub use models::Roster;
+pub use utils::{format_compact, format_decimal, format_number, ApiClient, NumberLocale};
@@ -1,900 +1,296 @@
-mod utils;
-use colored::Color;
 use eframe::{egui, App, CreationContext, Frame};
-use egui_extras::RetainedImage;
-use serde_json::Value;
-use std::collections::HashMap;
+use genshin_viewer::paths::Paths;
+use genshin_viewer::settings::Settings;
+use genshin_viewer::state::{AppState, Effect, Event, Phase, RequestId};
+use genshin_viewer::{image_cache, ui, uid, utils, Roster};
+use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 mod icons;
-use std::path::PathBuf;
-
-struct CharacterIcon {
-	texture: RetainedImage,
-	loading: bool,
-	error: bool,
-}
-
-struct StatIcons {
-	hp: RetainedImage,
-	atk: RetainedImage,
-	def: RetainedImage,
-	crit_rate: RetainedImage,
-	crit_dmg: RetainedImage,
-	er: RetainedImage,
-	em: RetainedImage,
-}
-
-impl StatIcons {
-	fn new() -> Self {
-		Self {
-			hp: RetainedImage::from_image_bytes("hp_icon", include_bytes!("../assets/icons/hp.png")).unwrap(),
-			atk: RetainedImage::from_image_bytes("atk_icon", include_bytes!("../assets/icons/atk.png")).unwrap(),
-			def: RetainedImage::from_image_bytes("def_icon", include_bytes!("../assets/icons/def.png")).unwrap(),
-			crit_rate: RetainedImage::from_image_bytes("crit_rate_icon", include_bytes!("../assets/icons/crit_rate.png")).unwrap(),
-			crit_dmg: RetainedImage::from_image_bytes("crit_dmg_icon", include_bytes!("../assets/icons/crit_dmg.png")).unwrap(),
-			er: RetainedImage::from_image_bytes("er_icon", include_bytes!("../assets/icons/er.png")).unwrap(),
-			em: RetainedImage::from_image_bytes("em_icon", include_bytes!("../assets/icons/em.png")).unwrap(),
-		}
-	}
-}
 
+const USAGE: &str = "Usage: genshin-viewer [--from-file FILE]...";
 
 struct MyApp {
-	characters: Option<Vec<Value>>,
-	calculations: Option<Value>,
-	selected_character: Option<usize>,
-	loading: bool,
-	error: Option<String>,
-	rx: Receiver<Result<(Vec<Value>, Value), String>>,
-	tx: Sender<Result<(Vec<Value>, Value), String>>,
-	icons: Arc<Mutex<HashMap<String, CharacterIcon>>>,
-	runtime: Arc<tokio::runtime::Runtime>,
-	stat_icons: StatIcons,
-	uid: Option<String>,
-	uid_input: String,
+	state: AppState,
+	assets: ui::Assets,
+	// Fetch results and loaded history come back through here
+	rx: Receiver<Event>,
+	tx: Sender<Event>,
+	runtime: tokio::runtime::Runtime,
+	client: utils::ApiClient,
+	// The base URL `client` was built with
+	api_url: String,
+	paths: Paths,
+	// The newest settings not on disk yet, and a lock so only one write runs at a time
+	unsaved_settings: Arc<Mutex<Option<String>>>,
+	settings_writer: Arc<Mutex<()>>,
+	// Responses not on disk yet, oldest first. History is read under the same lock
+	// so it sees every stored snapshot.
+	unsaved_responses: Arc<Mutex<VecDeque<(String, Roster)>>>,
+	response_writer: Arc<Mutex<()>>,
+	ctx: egui::Context,
+	native_pixels_per_point: f32,
+	// When the last fetch finished, for auto refresh
+	last_fetched: Instant,
 }
 
 impl MyApp {
-	fn get_config_dir() -> PathBuf {
-		if cfg!(windows) {
-			let local_app_data = std::env::var("LOCALAPPDATA")
-				.unwrap_or_else(|_| {
-					let user_profile = std::env::var("USERPROFILE")
-						.expect("Failed to get user profile directory");
-					format!("{}/AppData/Local", user_profile)
-				});
-			PathBuf::from(local_app_data).join("genshin-viewer")
-		} else {
-			let home = std::env::var("HOME")
-				.expect("Failed to get home directory");
-			PathBuf::from(home).join(".config").join("genshin-viewer")
+	fn new(cc: &CreationContext, files: Vec<PathBuf>) -> Self {
+		let paths = Paths::from_env();
+		if paths.temporary {
+			eprintln!("No home directory found, using {} for this session", std::env::temp_dir().display());
 		}
-	}
+		if let Some(legacy) = Paths::legacy_config(|key| std::env::var_os(key)) {
+			for moved in paths.migrate_from(&legacy) {
+				eprintln!("Moved {} from {}", moved.display(), legacy.display());
+			}
+		}
+		paths.create_dirs();
 
-	fn get_uid_file_path() -> PathBuf {
-		let config_dir = Self::get_config_dir();
-		std::fs::create_dir_all(&config_dir).ok();
-		config_dir.join("saved_uid.txt")
-	}
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let icons = image_cache::ImageCache::new(runtime.handle().clone()).with_disk_cache(paths.image_cache_dir());
 
-	fn new(cc: &CreationContext) -> Self {
-		let (tx, rx) = channel();
-		let rt = tokio::runtime::Runtime::new().unwrap();
-		
 		// Try to load saved UID
-		let uid = std::fs::read_to_string(Self::get_uid_file_path()).ok();
-		
+		let uid = std::fs::read_to_string(paths.uid_file())
+			.ok()
+			.map(|uid| uid.trim().to_string())
+			.filter(|uid| uid::validate(uid).is_ok());
+
+		let settings = std::fs::read_to_string(paths.settings_file())
+			.map(|text| Settings::from_json(&text))
+			.unwrap_or_default();
+
+		let cached = uid.as_deref().and_then(|uid| paths.cached_response(uid));
+		let (mut state, effects) = AppState::start(uid, settings);
+		if let Some(cached) = cached {
+			state.show_cached(cached);
+		}
+		let (tx, rx) = channel();
 		let mut app = Self {
-			characters: None,
-			calculations: None,
-			selected_character: None,
-			loading: false,
-			error: None,
+			state,
+			assets: ui::Assets::new(icons),
 			rx,
-			tx: tx.clone(),
-			icons: Arc::new(Mutex::new(HashMap::new())),
-			runtime: Arc::new(rt),
-			stat_icons: StatIcons::new(),
-			uid,
-			uid_input: String::new(),
+			tx,
+			runtime,
+			client: utils::ApiClient::default(),
+			api_url: utils::BASE_URL.to_string(),
+			paths,
+			unsaved_settings: Arc::default(),
+			settings_writer: Arc::default(),
+			unsaved_responses: Arc::default(),
+			response_writer: Arc::default(),
+			ctx: cc.egui_ctx.clone(),
+			native_pixels_per_point: cc.integration_info.native_pixels_per_point.unwrap_or(1.0),
+			last_fetched: Instant::now(),
 		};
-		
-		// If we have a saved UID, load the data
-		if app.uid.is_some() {
-			app.load_data();
+		app.apply_settings();
+		app.run_effects(effects);
+		if !files.is_empty() {
+			app.dispatch(Event::OpenFiles(files));
 		}
-		
 		app
 	}
 
-	fn load_data(&mut self) {
-		if let Some(uid) = &self.uid {
-			self.loading = true;
-			let uid_clone = uid.clone();
-			let rt = tokio::runtime::Runtime::new().unwrap();
-			let tx = self.tx.clone();
-
-			std::thread::spawn(move || {
-				rt.block_on(async {
-					match (
-						utils::get_user_builds(&uid_clone).await,
-						utils::get_user_calculations(&uid_clone).await,
-					) {
-						(Ok(chars), Ok(calcs)) => {
-							if let Some(char_array) = chars.as_array() {
-								tx.send(Ok((char_array.to_vec(), calcs))).unwrap();
-							}
-						}
-						_ => tx
-							.send(Err("Failed to fetch data".to_string()))
-							.unwrap(),
-					}
-				});
-			});
-			
-			// self.runtime.spawn(async move {
-			// 	match (
-			// 		utils::get_user_builds(&uid_clone).await,
-			// 		utils::get_user_calculations(&uid_clone).await,
-			// 	) {
-			// 		(Ok(chars), Ok(calcs)) => {
-			// 			if let Some(char_array) = chars.as_array() {
-			// 				tx.send(Ok((char_array.to_vec(), calcs))).unwrap();
-			// 			}
-			// 		}
-			// 		_ => tx.send(Err("Failed to fetch data".to_string())).unwrap(),
-			// 	}
-			// });
+	fn apply_settings(&mut self) {
+		// Visuals follow the selected character too, `ui::show` sets them every frame
+		let settings = &self.state.settings;
+		let pixels_per_point = self.native_pixels_per_point * settings.ui_scale;
+		if self.ctx.pixels_per_point() != pixels_per_point {
+			self.ctx.set_pixels_per_point(pixels_per_point);
 		}
-	}
-
-	async fn refresh_data(&self) -> Result<(), Box<dyn std::error::Error>> {
-		if let Some(uid) = &self.uid {
-			let url = format!("https://akasha.cv/api/user/refresh/{}", uid);
-			reqwest::get(&url).await?;
+		// A hand-edited settings file may hold anything
+		let api_url = settings
+			.api_base_url
+			.as_deref()
+			.filter(|url| utils::is_api_url(url))
+			.unwrap_or(utils::BASE_URL);
+		if api_url != self.api_url {
+			self.client = utils::ApiClient::new(api_url);
+			self.api_url = api_url.to_string();
 		}
-		Ok(())
-	}
-
-	fn save_uid(&self) {
-		if let Some(uid) = &self.uid {
-			std::fs::write(Self::get_uid_file_path(), uid).ok();
-		}
-	}
-
-	fn logout(&mut self) {
-		self.uid = None;
-		self.characters = None;
-		self.calculations = None;
-		self.selected_character = None;
-		self.uid_input.clear();
-		std::fs::remove_file(Self::get_uid_file_path()).ok();
+		self.assets.icons.set_limit(settings.cache_limit_bytes());
 	}
 
-	async fn load_icon(url: &str) -> Result<RetainedImage, Box<dyn std::error::Error>> {
-		let response = reqwest::get(url).await?;
-		let bytes = response.bytes().await?;
-		let image = RetainedImage::from_image_bytes(url, &bytes)?;
-		Ok(image)
+	fn dispatch(&mut self, event: Event) {
+		let effects = self.state.handle(event);
+		self.run_effects(effects);
 	}
 
-	fn ensure_icon(&self, icon_name: &str) {
-		let icons = self.icons.clone();
-		let icon_url = if icon_name.starts_with("http") {
-			icon_name.to_string()
-		} else {
-			format!("https://enka.network/ui/{}.png", icon_name)
-		};
-
-		if !icons.lock().unwrap().contains_key(&icon_url) {
-			icons.lock().unwrap().insert(
-				icon_url.clone(),
-				CharacterIcon {
-					texture: RetainedImage::from_color_image(
-						"loading",
-						egui::ColorImage::new([32, 32], egui::Color32::GRAY),
-					),
-					loading: true,
-					error: false,
-				},
-			);
-
-			let icons = icons.clone();
-			self.runtime.spawn(async move {
-				match Self::load_icon(&icon_url).await {
-					Ok(image) => {
-						let mut icons = icons.lock().unwrap();
-						if let Some(icon) = icons.get_mut(&icon_url) {
-							icon.texture = image;
-							icon.loading = false;
-						}
-					}
-					Err(_) => {
-						let mut icons = icons.lock().unwrap();
-						if let Some(icon) = icons.get_mut(&icon_url) {
-							icon.error = true;
-							icon.loading = false;
-						}
-					}
+	fn run_effects(&mut self, effects: Vec<Effect>) {
+		for effect in effects {
+			match effect {
+				Effect::Fetch { uid, request } => self.fetch(uid, request),
+				Effect::FetchCalculations { uid, request } => {
+					let tx = self.tx.clone();
+					let client = self.client.clone();
+					self.runtime.spawn(async move {
+						let result = Roster::fetch_calculations(&client, &uid).await;
+						tx.send(Event::CalculationsFetched { request, result }).ok();
+					});
 				}
-			});
-		}
-	}
-
-	fn load_all_icons(&self, value: &Value) {
-		if let Some(obj) = value.as_object() {
-			for (key, val) in obj {
-				if key == "icon" {
-					if let Some(icon) = val.as_str() {
-						self.ensure_icon(icon);
-					}
-				} else {
-					// Recursively check nested objects and arrays
-					match val {
-						Value::Object(_) => self.load_all_icons(&val),
-						Value::Array(arr) => {
-							for item in arr {
-								self.load_all_icons(item);
-							}
-						}
-						_ => {}
+				Effect::StoreResponse { uid } => {
+					if let Some(roster) = self.state.roster() {
+						self.unsaved_responses.lock().unwrap().push_back((uid, roster.clone()));
+						let unsaved = self.unsaved_responses.clone();
+						let writer = self.response_writer.clone();
+						let paths = self.paths.clone();
+						self.runtime.spawn_blocking(move || {
+							let _writing = writer.lock().unwrap();
+							store_responses(&paths, &unsaved);
+						});
 					}
 				}
-			}
-		} else if let Some(arr) = value.as_array() {
-			for item in arr {
-				self.load_all_icons(item);
-			}
-		}
-	}
-
-	fn render_character_list(&mut self, ui: &mut egui::Ui) {
-		if let Some(chars) = &self.characters {
-			// Pre-load all icons from the entire data structure
-			for char in chars.iter() {
-				self.load_all_icons(char);
-			}
-
-			egui::ScrollArea::vertical().show(ui, |ui| {
-				for (idx, char) in chars.iter().enumerate() {
-					let name = char["name"].as_str().unwrap_or("Unknown");
-					let element = char["characterMetadata"]["element"]
-						.as_str()
-						.unwrap_or("")
-						.to_lowercase();
-
-					let icon_url = if let Some(icon) = char["icon"].as_str() {
-						if icon.starts_with("http") {
-							icon.to_string()
-						} else {
-							format!("https://enka.network/ui/{}.png", icon)
-						}
-					} else {
-						String::new()
+				Effect::SaveUid(uid) => {
+					std::fs::write(self.paths.uid_file(), uid).ok();
+				}
+				Effect::ForgetUid => {
+					std::fs::remove_file(self.paths.uid_file()).ok();
+				}
+				Effect::Export { uid, selected } => {
+					let Some(roster) = self.state.roster() else {
+						continue;
 					};
-
-					let element_color = match element.as_str() {
-						"hydro" => egui::Color32::from_rgb(0, 144, 255),
-						"pyro" => egui::Color32::from_rgb(255, 69, 0),
-						"cryo" => egui::Color32::from_rgb(167, 223, 236),
-						"electro" => egui::Color32::from_rgb(178, 132, 255),
-						"anemo" => egui::Color32::from_rgb(148, 255, 198),
-						"geo" => egui::Color32::from_rgb(255, 198, 93),
-						"dendro" => egui::Color32::from_rgb(147, 215, 65),
-						_ => egui::Color32::WHITE,
+					let selected = selected.and_then(|idx| self.state.view()?.build(roster, idx));
+					let notice = match self.paths.export(&uid, roster, selected) {
+						Ok(file) => format!("Exported to {}", file.display()),
+						Err(e) => format!("Export failed: {}", e),
 					};
-
-					let is_selected = self.selected_character == Some(idx);
-
-					ui.horizontal(|ui| {
-						// Get icon from cache
-						if !icon_url.is_empty() {
-							if let Some(icon) = self.icons.lock().unwrap().get(&icon_url) {
-								let size = 32.0;
-								icon.texture.show_size(ui, egui::vec2(size, size));
-							}
-						}
-
-						if ui
-							.selectable_label(
-								is_selected,
-								egui::RichText::new(name).color(element_color),
-							)
-							.clicked()
-						{
-							self.selected_character = Some(idx);
+					self.dispatch(Event::Notice(notice));
+				}
+				Effect::LoadHistory { uid } => {
+					let tx = self.tx.clone();
+					let paths = self.paths.clone();
+					let unsaved = self.unsaved_responses.clone();
+					let writer = self.response_writer.clone();
+					self.runtime.spawn_blocking(move || {
+						let _writing = writer.lock().unwrap();
+						store_responses(&paths, &unsaved);
+						let snapshots = paths.load_history(&uid);
+						tx.send(Event::HistoryLoaded { uid, snapshots }).ok();
+					});
+				}
+				Effect::OpenFiles(files) => {
+					let tx = self.tx.clone();
+					self.runtime.spawn_blocking(move || {
+						let result = Roster::open(&files);
+						tx.send(Event::Opened { files, result }).ok();
+					});
+				}
+				Effect::SaveSettings => {
+					// Written off the UI thread. A write that finds nothing left to save
+					// was overtaken by a later one, so the newest settings always win.
+					*self.unsaved_settings.lock().unwrap() = Some(self.state.settings.to_json());
+					let unsaved = self.unsaved_settings.clone();
+					let writer = self.settings_writer.clone();
+					let file = self.paths.settings_file();
+					self.runtime.spawn_blocking(move || {
+						let _writing = writer.lock().unwrap();
+						if let Some(json) = unsaved.lock().unwrap().take() {
+							std::fs::write(file, json).ok();
 						}
 					});
+					self.apply_settings();
 				}
-			});
+			}
 		}
 	}
 
-	fn render_constellations(&self, ui: &mut egui::Ui, short_name: &str, constellation: i64) {
-		ui.vertical(|ui| {
-			for i in 1..=6 {
-				let cons_url = format!("https://enka.network/ui/UI_Talent_S_{}_{:02}.png", short_name, i);
-				self.ensure_icon(&cons_url);
-
-				if let Some(icon) = self.icons.lock().unwrap().get(&cons_url) {
-					let size = 48.0;
-					
-					if i <= constellation {
-						icon.texture.show_size(ui, egui::vec2(size, size));
-					}
+	// Both requests run at once. The builds show as soon as they arrive, the
+	// calculations are sent after them so the state always has a roster to fill in.
+	fn fetch(&self, uid: String, request: RequestId) {
+		let tx = self.tx.clone();
+		let client = self.client.clone();
+		self.runtime.spawn(async move {
+			let builds = async {
+				let (result, warning) = match Roster::fetch_builds(&client, &uid).await {
+					Ok((roster, warning)) => (Ok(roster), warning),
+					Err(error) => (Err(error), None),
+				};
+				let ok = result.is_ok();
+				// The app may already be gone
+				tx.send(Event::Fetched { request, result }).ok();
+				if let Some(warning) = warning {
+					tx.send(Event::Notice(warning)).ok();
 				}
-				ui.add_space(4.0); // Small space between constellation icons
+				ok
+			};
+			let (ok, result) = tokio::join!(builds, Roster::fetch_calculations(&client, &uid));
+			if ok {
+				tx.send(Event::CalculationsFetched { request, result }).ok();
 			}
 		});
 	}
-
-	fn find_by_character_id(&self, target_id: i64) -> Option<&Value> {
-		self.calculations.as_ref().and_then(|calcs| {
-			calcs.as_array()?.iter().find(|calc| {
-				calc["characterId"].as_i64() == Some(target_id)
-			})
-		})
-	}
-
-	fn render_character_details(&self, ui: &mut egui::Ui) {
-		if let Some(idx) = self.selected_character {
-			if let Some(chars) = &self.characters {
-				if let Some(char) = chars.get(idx) {
-					// Get character name and constellation level
-					let name = char["name"].as_str().unwrap_or("Unknown");
-					let constellation = char["constellation"].as_i64().unwrap_or(0);
-					let short_name = char.get("icon")
-						.and_then(|i| i.as_str())
-						.unwrap()
-						.rsplit('_')
-						.next()
-						.and_then(|part| part.split('.').next())
-						.unwrap();
-
-					// println!("{}", serde_json::to_string_pretty(&char).unwrap());
-
-					// Create right-side overlay for constellations and name
-					let screen_rect = ui.max_rect();
-					
-					// Constellation panel - center-right
-					let cons_width = 64.0;
-					let cons_rect = egui::Rect::from_min_max(
-						egui::pos2(screen_rect.right() - cons_width - 20.0, screen_rect.center().y - 180.0),
-						egui::pos2(screen_rect.right() - 20.0, screen_rect.center().y + 180.0),
-					);
-					
-					// Name panel - bottom-right
-					let name_height = 40.0;
-					let name_rect = egui::Rect::from_min_max(
-						egui::pos2(screen_rect.right() - 300.0, screen_rect.bottom() - name_height - 20.0),
-						egui::pos2(screen_rect.right() - 20.0, screen_rect.bottom() - 20.0),
-					);
-					let ranking_height = 40.0;
-					let ranking_rect = egui::Rect::from_min_max(
-						egui::pos2(screen_rect.right() - 300.0, screen_rect.top() + ranking_height + 20.0),
-						egui::pos2(screen_rect.right() - 20.0, screen_rect.bottom() - 20.0),
-					);
-
-					// Render constellations
-					let cons_response = egui::Area::new("constellations")
-						.fixed_pos(cons_rect.min)
-						.show(ui.ctx(), |ui| {
-							self.render_constellations(ui, short_name, constellation);
-						});
-
-					// Render name
-					egui::Area::new("character_name")
-						.fixed_pos(name_rect.min)
-						.show(ui.ctx(), |ui| {
-							ui.heading(egui::RichText::new(name)
-								.size(32.0)
-								.strong());
-						});
-
-					// Render Ranking
-					egui::Area::new("ranking")
-						.fixed_pos(ranking_rect.min)
-						.show(ui.ctx(), |ui| {
-							// println!("Character data: {:#?}", char);  // Debug print the entire character data
-							
-							if let Some(char_id) = char["characterId"].as_i64() {
-								if let Some(calculation) = self.find_by_character_id(char_id) {
-									// println!("Found calculation: {:#?}", calculation);
-									
-									if let Some(calc) = calculation.get("calculations").and_then(|c| c.get("fit")).and_then(|f| f.as_object()) {
-										// println!("{:#?}", calc);
-										if let (Some(rank), Some(total)) = (
-											calc.get("ranking").and_then(|v| v.as_i64()),
-											calc.get("outOf").and_then(|v| v.as_i64())
-										) {
-											let percentage = (rank as f64 / total as f64 * 100.0) as i64;
-											ui.heading(egui::RichText::new(
-												format!("Top {}% ({}/{})", percentage, rank, total)
-											)
-												.size(22.0)
-												.strong());
-										}
-									}
-								} else {
-									println!("No calculation found for character ID: {}", char_id);
-								}
-							} else {
-								println!("Could not find characterId as number in: {:#?}", char.get("characterId"));
-							}
-						});
-
-					let element = char["characterMetadata"]["element"]
-						.as_str()
-						.unwrap_or("")
-						.to_lowercase();
-					
-					let bg_url = format!("https://akasha.cv/elementalBackgrounds/{}-bg.jpg", 
-						element.chars().next().map(|c| c.to_uppercase().collect::<String>()).unwrap_or_default() + &element[1..]);
-					self.ensure_icon(&bg_url);
-
-					let rect = ui.available_rect_before_wrap();
-					
-					// Paint background if available
-					if let Some(bg) = self.icons.lock().unwrap().get(&bg_url) {
-						if !bg.loading && !bg.error {
-							let img_size = bg.texture.size_vec2();
-							let img_aspect = img_size.x / img_size.y;
-							let rect_aspect = rect.width() / rect.height();
-							
-							let uv_rect = if img_aspect > rect_aspect {
-								// Image is wider than container - crop sides
-								let uv_width = rect_aspect / img_aspect;
-								let uv_x = (1.0 - uv_width) / 2.0;
-								egui::Rect::from_min_max(
-									egui::pos2(uv_x, 0.0),
-									egui::pos2(uv_x + uv_width, 1.0)
-								)
-							} else {
-								// Image is taller than container - crop top/bottom
-								let uv_height = img_aspect / rect_aspect;
-								let uv_y = (1.0 - uv_height) / 2.0;
-								egui::Rect::from_min_max(
-									egui::pos2(0.0, uv_y),
-									egui::pos2(1.0, uv_y + uv_height)
-								)
-							};
-
-							ui.painter().image(
-								bg.texture.texture_id(ui.ctx()),
-								rect,
-								uv_rect,
-								egui::Color32::WHITE,
-							);
-							
-							// Add overlay
-							ui.painter().rect_filled(
-								rect,
-								0.0,
-								egui::Color32::from_black_alpha(180),
-							);
-						}
-					}
-
-					if let Some(name) = char.get("icon").and_then(|i| i.as_str()) {
-						let icon_url = format!("https://enka.network/ui/UI_Gacha_AvatarImg_{}.png", short_name);
-						self.ensure_icon(&icon_url);
-
-						if let Some(icon) = self.icons.lock().unwrap().get(&icon_url) {
-							if !icon.loading && !icon.error {
-								// let rect = ui.available_rect_before_wrap();
-								let img_size = icon.texture.size_vec2();
-								let img_aspect = img_size.x / img_size.y;
-								let rect_aspect = rect.width() / rect.height();
-								
-								let uv_rect = if img_aspect > rect_aspect {
-									// Image is wider than container - crop sides
-									let uv_width = rect_aspect / img_aspect;
-									let uv_x = (1.0 - uv_width) / 2.0;
-									egui::Rect::from_min_max(
-										egui::pos2(uv_x, 0.0),
-										egui::pos2(uv_x + uv_width, 1.0)
-									)
-								} else {
-									// Image is taller than container - crop top/bottom
-									let uv_height = img_aspect / rect_aspect;
-									let uv_y = (1.0 - uv_height) / 2.0;
-									egui::Rect::from_min_max(
-										egui::pos2(0.0, uv_y),
-										egui::pos2(1.0, uv_y + uv_height)
-									)
-								};
-
-								ui.painter().image(
-									icon.texture.texture_id(ui.ctx()),
-									rect,
-									uv_rect,
-									egui::Color32::WHITE,
-								);
-							} else if icon.loading {
-								ui.spinner();
-								ui.label("Loading character art...");
-							} else {
-								ui.label("Failed to load character art");
-							}
-						}
-					}
-
-					let full_height = ui.available_height();
-
-					// Continue with existing UI
-					ui.horizontal(|ui| {
-						// Left panel for avatar (40% width)
-
-						// Right panel for character details (60% width)
-						egui::Frame::none()
-							.inner_margin(10.0)
-							.show(ui, |ui| {
-								ui.vertical(|ui| {
-										// Card 1: Character Info and Talents
-										{
-											egui::Frame::none()
-												// .fill(ui.style().visuals.extreme_bg_color)
-												// .rounding(10.0)
-												// .stroke(ui.style().visuals.widgets.noninteractive.bg_stroke)
-												.inner_margin(10.0)
-												.show(ui, |ui| {
-													ui.set_min_width(200.0);
-													ui.vertical(|ui| {
-														ui.heading("Character Info");
-														ui.label(format!(
-															"Level {}/90",
-															char["propMap"]["level"]["val"].as_str().unwrap()
-														));
-														ui.label(format!("Constellation: C{}", char["constellation"].as_i64().unwrap_or(0)));
-
-														ui.add_space(10.0);
-														ui.heading("Talents");
-														// First get the normal attack icon based on weapon type
-														let normal_attack_icon = match char["weaponType"].as_str().unwrap_or("") {
-															"WeAPON_SWORD_ONE_HAND" => "Skill_A_01",
-															"WeAPON_BOW" => "Skill_A_02",
-															"WeAPON_POLE" => "Skill_A_03",
-															"WeAPON_CLAYMORE" => "Skill_A_04",
-															"WeAPON_CATALYST" => "Skill_A_05",
-															_ => "Skill_A_01", // default to sword if unknown
-														};
-
-														// Generate URLs for all three talent icons
-														let normal_attack_url = format!("https://enka.network/ui/{}.png", normal_attack_icon);
-														let skill_url = format!("https://enka.network/ui/Skill_S_{}_01.png", short_name);
-														let burst_url = format!("https://enka.network/ui/Skill_E_{}_01.png", short_name);
-
-														// Ensure all talent icons are loaded
-														self.ensure_icon(&normal_attack_url);
-														self.ensure_icon(&skill_url);
-														self.ensure_icon(&burst_url);
-
-														ui.horizontal(|ui| {
-															if let Some(icon) = self.icons.lock().unwrap().get(&normal_attack_url) {
-																let size = 32.0;
-																icon.texture.show_size(ui, egui::vec2(size, size));
-															}
-															ui.label(format!(
-																"Normal Attack: {}",
-																char["talentsLevelMap"]["normalAttacks"]["level"].as_i64().unwrap_or(0)
-															));
-														});
-
-														ui.horizontal(|ui| {
-															if let Some(icon) = self.icons.lock().unwrap().get(&skill_url) {
-																let size = 32.0;
-																icon.texture.show_size(ui, egui::vec2(size, size));
-															}
-															ui.label(format!(
-																"Elemental Skill: {}",
-																char["talentsLevelMap"]["elementalSkill"]["level"].as_i64().unwrap_or(0)
-															));
-														});
-
-														ui.horizontal(|ui| {
-															if let Some(icon) = self.icons.lock().unwrap().get(&burst_url) {
-																let size = 32.0;
-																icon.texture.show_size(ui, egui::vec2(size, size));
-															}
-															ui.label(format!(
-																"Elemental Burst: {}",
-																char["talentsLevelMap"]["elementalBurst"]["level"].as_i64().unwrap_or(0)
-															));
-														});
-													});
-												});
-										}
-
-										// Card 2: Weapon Info
-										{
-											egui::Frame::none()
-												// .fill(ui.style().visuals.extreme_bg_color)
-												// .rounding(10.0)
-												.inner_margin(10.0)
-												// .stroke(ui.style().visuals.widgets.noninteractive.bg_stroke)
-												.show(ui, |ui| {
-													ui.vertical(|ui| {
-														ui.heading("Weapon");
-														if let Some(weapon) = char["weapon"].as_object() {
-															if let Some(icon) = weapon.get("icon").and_then(|i| i.as_str()) {
-																let icon_url = if icon.starts_with("http") {
-																	icon.to_string()
-																} else {
-																	format!("https://enka.network/ui/{}.png", icon)
-																};
-																if let Some(icon) = self.icons.lock().unwrap().get(&icon_url) {
-																	let size = 64.0;
-																	icon.texture.show_size(ui, egui::vec2(size, size));
-																}
-															}
-															ui.label(format!(
-																"{} R{}",
-																weapon["name"].as_str().unwrap_or(""),
-																weapon["weaponInfo"]["refinementLevel"]["value"].as_i64().unwrap_or(0) + 1
-															));
-															ui.label(format!(
-																"Level {}/90",
-																weapon["weaponInfo"]["level"].as_i64().unwrap_or(0)
-															));
-														}
-													});
-												});
-										}
-
-										ui.end_row();
-
-										// Card 3: Stats
-										{
-											egui::Frame::none()
-												// .fill(ui.style().visuals.extreme_bg_color)
-												// .rounding(10.0)
-												.inner_margin(10.0)
-												// .stroke(ui.style().visuals.widgets.noninteractive.bg_stroke)
-												.show(ui, |ui| {
-													ui.vertical(|ui| {
-														ui.heading("Stats");
-														
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.hp.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("HP | {}", utils::format_number(char["stats"]["maxHp"]["value"].as_f64().unwrap_or(0.0))));
-														});
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.atk.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("ATK | {}", utils::format_number(char["stats"]["atk"]["value"].as_f64().unwrap_or(0.0))));
-														});
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.def.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("DEF | {}", utils::format_number(char["stats"]["def"]["value"].as_f64().unwrap_or(0.0))));
-														});
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.crit_rate.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("Crit Rate | {:.1}%", char["stats"]["critRate"]["value"].as_f64().unwrap_or(0.0) * 100.0));
-														});
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.crit_dmg.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("Crit DMG | {:.1}%", char["stats"]["critDamage"]["value"].as_f64().unwrap_or(0.0) * 100.0));
-														});
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.er.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("Energy Recharge | {:.1}%", char["stats"]["energyRecharge"]["value"].as_f64().unwrap_or(0.0) * 100.0));
-														});
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.em.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("Elemental Mastery | {}", char["stats"]["elementalMastery"]["value"].as_f64().unwrap_or(0.0) as i64));
-														});
-													});
-												});
-										}
-
-										// Card 4: Artifacts and Build Quality
-										{
-											egui::Frame::none()
-												// .fill(ui.style().visuals.extreme_bg_color)
-												// .rounding(10.0)
-												.inner_margin(10.0)
-												// .stroke(ui.style().visuals.widgets.noninteractive.bg_stroke)
-												.show(ui, |ui| {
-													ui.vertical(|ui| {
-
-														ui.heading("Artifact Sets");
-														if let Some(sets) = char["artifactSets"].as_object() {
-															for (name, details) in sets {
-																if let Some(icon) = details.get("icon").and_then(|i| i.as_str()) {
-																	ui.horizontal(|ui| {
-																		let icon_url = if icon.starts_with("http") {
-																			icon.to_string()
-																		} else {
-																			format!("https://enka.network/ui/{}.png", icon)
-																		};
-																		if let Some(icon) = self.icons.lock().unwrap().get(&icon_url) {
-																			let size = 32.0;
-																			icon.texture.show_size(ui, egui::vec2(size, size));
-																		}
-																		ui.label(format!("{} ({}pc)", name, details["count"].as_i64().unwrap_or(0)));
-																	});
-																} else {
-																	ui.label(format!("{} ({}pc)", name, details["count"].as_i64().unwrap_or(0)));
-																}
-															}
-														}
-
-														ui.add_space(10.0);
-														ui.heading("Build Quality");
-														
-
-														ui.horizontal(|ui| {
-															let size = 16.0;
-															self.stat_icons.crit_dmg.show_size(ui, egui::vec2(size, size));
-															ui.label(format!("Crit Value | {:.2}", char["critValue"].as_f64().unwrap_or(0.0)));
-														});
-													});
-												});
-										}
-									});
-						});
-					});
-				}
-			}
-		}
-	}
 }
 
 impl App for MyApp {
 	fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
-		if self.uid.is_none() {
-			// Show UID input dialog
-			egui::CentralPanel::default().show(ctx, |ui| {
-				ui.vertical_centered(|ui| {
-					ui.add_space(100.0);
-					ui.heading("Enter your Genshin Impact UID");
-					ui.add_space(20.0);
-					
-					let text_edit = ui.add(egui::TextEdit::singleline(&mut self.uid_input)
-						.hint_text("Enter UID...")
-						.desired_width(200.0));
-					
-					if text_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-						if !self.uid_input.is_empty() {
-							self.uid = Some(self.uid_input.clone());
-							self.save_uid();
-							self.load_data();
-						}
-					}
-				});
-			});
-			return;
+		while let Ok(event) = self.rx.try_recv() {
+			if matches!(event, Event::Fetched { .. }) {
+				self.last_fetched = Instant::now();
+			}
+			self.dispatch(event);
 		}
 
-		// Handle loading results
-		if self.loading {
-			if let Ok(result) = self.rx.try_recv() {
-				match result {
-					Ok((chars, calcs)) => {
-						self.characters = Some(chars);
-						self.calculations = Some(calcs);
-						self.loading = false;
-					}
-					Err(e) => {
-						self.error = Some(e);
-						self.loading = false;
-					}
-				}
-			}
+		let auto_refresh = Duration::from_secs(self.state.settings.auto_refresh_minutes as u64 * 60);
+		if !auto_refresh.is_zero()
+			&& matches!(self.state.phase, Phase::Loaded { .. })
+			&& self.last_fetched.elapsed() >= auto_refresh
+		{
+			self.last_fetched = Instant::now();
+			self.dispatch(Event::Refresh);
 		}
 
-		// Main UI
-		egui::SidePanel::left("character_list")
-			.default_width(200.0)
-			.show(ctx, |ui| {
-				ui.vertical(|ui| {
-					ui.heading("Characters");
-					
-					// Add refresh and logout buttons
-					if ui.button("🔄 Refresh").clicked() {
-						let runtime = self.runtime.clone();
-						let tx = self.tx.clone();
-						let rt = tokio::runtime::Runtime::new().unwrap();
-						if let Some(uid) = &self.uid {
-							let uid_clone = uid.clone();
-							self.loading = true;
-							// let tx = self.tx.clone();
+		for event in ui::show(ctx, &self.state, &self.assets) {
+			self.dispatch(event);
+		}
 
-							std::thread::spawn(move || {
-								rt.block_on(async {
-									match (
-										utils::get_user_builds(&uid_clone).await,
-										utils::get_user_calculations(&uid_clone).await,
-									) {
-										(Ok(chars), Ok(calcs)) => {
-											if let Some(char_array) = chars.as_array() {
-												tx.send(Ok((char_array.to_vec(), calcs))).unwrap();
-											}
-										}
-										_ => tx
-											.send(Err("Failed to fetch data".to_string()))
-											.unwrap(),
-									}
-								});
-							});
-							// runtime.spawn(async move {
-							// 	match utils::refresh_user(&uid_clone).await {
-							// 		Ok(()) => {
-							// 			// Wait a bit for the refresh to take effect
-							// 			tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-										
-							// 			match (
-							// 				utils::get_user_builds(&uid_clone).await,
-							// 				utils::get_user_calculations(&uid_clone).await,
-							// 			) {
-							// 				(Ok(chars), Ok(calcs)) => {
-							// 					if let Some(char_array) = chars.as_array() {
-							// 						tx.send(Ok((char_array.to_vec(), calcs))).unwrap();
-							// 					}
-							// 				}
-							// 				_ => tx.send(Err("Failed to fetch data".to_string())).unwrap(),
-							// 			}
-							// 		}
-							// 		Err(e) => tx.send(Err(format!("Failed to refresh: {}", e))).unwrap(),
-							// 	}
-							// });
-						}
-					}
-					
-					if ui.button("🚪 Logout").clicked() {
-						self.logout();
-						return;
-					}
-					
-					ui.separator();
-					
-					if self.loading {
-						ui.spinner();
-					} else if let Some(error) = &self.error {
-						ui.colored_label(egui::Color32::RED, error);
-					} else {
-						self.render_character_list(ui);
-					}
-				});
-			});
+		ctx.request_repaint();
+	}
+}
 
-		// Then render the panels on top
-		egui::CentralPanel::default().show(ctx, |ui| {
-			if self.loading {
-				ui.spinner();
-				ui.label("Loading character data...");
-			} else if let Some(error) = &self.error {
-				ui.colored_label(egui::Color32::RED, error);
-			} else if self.selected_character.is_none() {
-				ui.label("Select a character from the list");
-			} else {
-				self.render_character_details(ui);
-			}
-		});
+// Saved JSON to open instead of the saved UID
+// Call with the response writer locked
+fn store_responses(paths: &Paths, unsaved: &Mutex<VecDeque<(String, Roster)>>) {
+	loop {
+		let next = unsaved.lock().unwrap().pop_front();
+		let Some((uid, roster)) = next else {
+			break;
+		};
+		paths.store_response(&uid, &roster).ok();
+	}
+}
 
-		ctx.request_repaint();
+fn parse_args() -> Result<Vec<PathBuf>, String> {
+	let mut files = Vec::new();
+	let mut argv = std::env::args_os().skip(1);
+	while let Some(arg) = argv.next() {
+		match arg.to_str() {
+			Some("--from-file") => files.push(argv.next().ok_or("--from-file needs a value")?.into()),
+			Some("-h" | "--help") => return Err(USAGE.to_string()),
+			_ => return Err(format!("Unknown argument {}\n{}", arg.to_string_lossy(), USAGE)),
+		}
 	}
+	Ok(files)
 }
 
 fn main() -> Result<(), eframe::Error> {
-	let options = eframe::NativeOptions::default();
+	let files = match parse_args() {
+		Ok(files) => files,
+		Err(message) => {
+			eprintln!("{}", message);
+			std::process::exit(2);
+		}
+	};
+	let options = eframe::NativeOptions {
+		drag_and_drop_support: true,
+		..Default::default()
+	};
 
 	eframe::run_native(
 		"Genshin Character Viewer",
 		options,
-		Box::new(|cc| Box::new(MyApp::new(cc))),
+		Box::new(|cc| Box::new(MyApp::new(cc, files))),
 	)
 }
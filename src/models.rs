@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::stats::{self, StatKind};
+use crate::utils::{self, ApiClient};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+	// Akasha's own order
+	#[default]
+	CritValue,
+	Name,
+	Level,
+	Element,
+}
+
+impl SortOrder {
+	pub const ALL: [SortOrder; 4] = [SortOrder::CritValue, SortOrder::Name, SortOrder::Level, SortOrder::Element];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			SortOrder::CritValue => "Crit Value",
+			SortOrder::Name => "Name",
+			SortOrder::Level => "Level",
+			SortOrder::Element => "Element",
+		}
+	}
+}
+
+// Everything akasha knows about one account: a build per character plus the
+// leaderboard calculations, which only exist for some of them
+#[derive(Clone)]
+pub struct Roster {
+	pub characters: Vec<Value>,
+	pub calculations: Value,
+}
+
+impl Roster {
+	// Errors are flattened to strings straight away so the future stays `Send`.
+	// Calculations come separately, see `fetch_calculations`. The warning says when
+	// not every build could be read.
+	pub async fn fetch_builds(client: &ApiClient, uid: &str) -> Result<(Self, Option<String>), String> {
+		let (builds, truncated) = client.get_user_builds(uid).await.map_err(|e| format!("Failed to fetch builds: {}", e))?;
+		let Some(characters) = builds.as_array() else {
+			return Err("No builds found for this UID".to_string());
+		};
+		let warning = truncated.then(|| format!("Only the first {} builds were read, later ones are missing", characters.len()));
+		let roster = Self {
+			characters: characters.to_vec(),
+			calculations: Value::Null,
+		};
+		Ok((roster, warning))
+	}
+
+	pub async fn fetch_calculations(client: &ApiClient, uid: &str) -> Result<Value, String> {
+		client
+			.get_user_calculations(uid)
+			.await
+			.map_err(|e| format!("Failed to fetch calculations: {}", e))
+	}
+
+	// Both at once. The builds are still returned when only the calculations fail,
+	// with that error among the warnings.
+	pub async fn fetch(client: &ApiClient, uid: &str) -> Result<(Self, Vec<String>), String> {
+		let (builds, calculations) = tokio::join!(Self::fetch_builds(client, uid), Self::fetch_calculations(client, uid));
+		let (mut roster, warning) = builds?;
+		let mut warnings: Vec<String> = warning.into_iter().collect();
+		match calculations {
+			Ok(calculations) => roster.calculations = calculations,
+			Err(error) => warnings.push(format!("{}, showing builds without leaderboard results", error)),
+		}
+		Ok((roster, warnings))
+	}
+
+	// The shape kept in the response cache and history snapshots
+	pub fn to_json(&self) -> Value {
+		serde_json::json!({
+			"builds": self.characters,
+			"calculations": self.calculations,
+		})
+	}
+
+	pub fn from_json(json: &Value) -> Option<Self> {
+		Some(Self {
+			characters: json["builds"].as_array()?.to_vec(),
+			calculations: json["calculations"].clone(),
+		})
+	}
+
+	// Saved JSON: the API responses for builds and calculations (either or both), the
+	// cache and history shape, or a single exported build
+	pub fn from_dumps(docs: &[Value]) -> Result<Self, String> {
+		let mut characters = Vec::new();
+		let mut calculations = Vec::new();
+		for doc in docs {
+			if let Some(roster) = Roster::from_json(doc) {
+				characters.extend(roster.characters);
+				calculations.extend(roster.calculations.as_array().cloned().unwrap_or_default());
+			} else if doc["build"].is_object() {
+				characters.push(doc["build"].clone());
+				if doc["calculation"].is_object() {
+					calculations.push(doc["calculation"].clone());
+				}
+			} else {
+				let entries = doc["data"].as_array().or(doc.as_array()).ok_or("Not akasha build or calculation JSON")?;
+				for entry in entries {
+					// Only calculation entries carry the leaderboard results
+					if entry.get("calculations").is_some() {
+						calculations.push(entry.clone());
+					} else {
+						characters.push(entry.clone());
+					}
+				}
+			}
+		}
+		// Calculations alone are enough, their characters get stand-in builds
+		if characters.is_empty() && calculations.is_empty() {
+			return Err("No builds or calculations in the opened JSON".to_string());
+		}
+		Ok(Self {
+			characters,
+			calculations: Value::Array(calculations),
+		})
+	}
+
+	pub fn open(files: &[PathBuf]) -> Result<Self, String> {
+		let docs = files
+			.iter()
+			.map(|file| {
+				let text = std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+				serde_json::from_str(&text).map_err(|e| format!("{}: {}", file.display(), e))
+			})
+			.collect::<Result<Vec<Value>, String>>()?;
+		Self::from_dumps(&docs)
+	}
+
+	// Indices into `characters`, so a selection survives re-sorting
+	pub fn sorted_indices(&self, sort: SortOrder) -> Vec<usize> {
+		sorted_indices(&self.characters.iter().collect::<Vec<_>>(), sort)
+	}
+
+	pub fn find_calculation(&self, character_id: i64, build_type: &str) -> Option<&Value> {
+		self.calculations
+			.as_array()?
+			.iter()
+			.find(|calc| calc["characterId"].as_i64() == Some(character_id) && self::build_type(calc) == build_type)
+	}
+
+	pub fn calculation_for(&self, char: &Value) -> Option<&Value> {
+		self.find_calculation(char["characterId"].as_i64()?, build_type(char))
+	}
+
+	pub fn weapon_stats(&self, char: &Value) -> Vec<(StatKind, f64)> {
+		weapon_stats(char, self.calculation_for(char))
+	}
+}
+
+// "current" for the build the character wears, anything else is a saved variant
+pub fn build_type(build: &Value) -> &str {
+	build["type"].as_str().unwrap_or("current")
+}
+
+pub fn sorted_indices(chars: &[&Value], sort: SortOrder) -> Vec<usize> {
+	let name = |idx: &usize| chars[*idx]["name"].as_str().unwrap_or("").to_string();
+	let mut indices: Vec<usize> = (0..chars.len()).collect();
+	match sort {
+		SortOrder::CritValue => indices.sort_by(|a, b| {
+			let crit_value = |idx: &usize| chars[*idx]["critValue"].as_f64().unwrap_or(0.0);
+			crit_value(b).total_cmp(&crit_value(a))
+		}),
+		SortOrder::Name => indices.sort_by_key(name),
+		SortOrder::Level => indices.sort_by_key(|idx| {
+			let level = utils::LevelInfo::character(chars[*idx]).map(|level| level.level);
+			(std::cmp::Reverse(level), name(idx))
+		}),
+		SortOrder::Element => indices.sort_by_key(|idx| {
+			let element = chars[*idx]["characterMetadata"]["element"].as_str().unwrap_or("");
+			(element.to_string(), name(idx))
+		}),
+	}
+	indices
+}
+
+// Builds usually omit weapon stats, the calculations payload has them
+pub fn weapon_stats(char: &Value, calc: Option<&Value>) -> Vec<(StatKind, f64)> {
+	char["weapon"]["flat"]["weaponStats"]
+		.as_array()
+		.or_else(|| calc?["weapon"]["flat"]["weaponStats"].as_array())
+		.map(|entries| entries.iter().filter_map(stats::weapon_stat).collect())
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::{fixture_roster, temp_dir, MockServer, Reply, FIXTURE_BUILDS, FIXTURE_CALCULATIONS, FIXTURE_UID};
+	use crate::view::RosterView;
+	use std::time::{Duration, Instant};
+
+	fn names(roster: &Roster, sort: SortOrder) -> Vec<&str> {
+		roster
+			.sorted_indices(sort)
+			.into_iter()
+			.map(|idx| roster.characters[idx]["name"].as_str().unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn sorts_by_every_order() {
+		let roster = fixture_roster();
+		for sort in SortOrder::ALL {
+			let mut indices = roster.sorted_indices(sort);
+			indices.sort();
+			assert_eq!(indices, (0..roster.characters.len()).collect::<Vec<_>>(), "{:?}", sort);
+		}
+
+		let by_name = names(&roster, SortOrder::Name);
+		assert!(by_name.windows(2).all(|pair| pair[0] <= pair[1]));
+		assert_eq!(names(&roster, SortOrder::CritValue)[0], roster.characters[0]["name"]);
+	}
+
+	#[test]
+	fn reads_every_saved_shape() {
+		let fixture = fixture_roster();
+		let builds: Value = serde_json::from_str(FIXTURE_BUILDS).unwrap();
+		let calculations: Value = serde_json::from_str(FIXTURE_CALCULATIONS).unwrap();
+		let count = |roster: &Roster| (roster.characters.len(), roster.calculations.as_array().unwrap().len());
+		let expected = count(&fixture);
+
+		// In either order
+		let pair = Roster::from_dumps(&[calculations.clone(), builds.clone()]).unwrap();
+		assert_eq!(count(&pair), expected);
+		assert_eq!(pair.weapon_stats(&pair.characters[0]), fixture.weapon_stats(&fixture.characters[0]));
+		assert_eq!(count(&Roster::from_dumps(&[builds]).unwrap()), (expected.0, 0));
+		assert_eq!(count(&Roster::from_dumps(&[fixture.to_json()]).unwrap()), expected);
+
+		let export = serde_json::json!({ "build": fixture.characters[0], "calculation": null });
+		assert_eq!(count(&Roster::from_dumps(&[export]).unwrap()), (1, 0));
+
+		let leaderboard = Roster::from_dumps(&[calculations]).unwrap();
+		assert_eq!(count(&leaderboard), (0, expected.1));
+		assert_eq!(RosterView::new(&leaderboard).characters.len(), expected.1);
+
+		assert!(Roster::from_dumps(&[serde_json::json!({ "data": [] })]).is_err());
+		assert!(Roster::from_dumps(&[serde_json::json!({ "hello": "world" })]).is_err());
+	}
+
+	#[test]
+	fn open_names_the_bad_file() {
+		let dir = temp_dir("open");
+		let builds = dir.join("builds.json");
+		let broken = dir.join("broken.json");
+		std::fs::write(&builds, FIXTURE_BUILDS).unwrap();
+		std::fs::write(&broken, "{").unwrap();
+
+		let error = Roster::open(&[builds.clone(), broken.clone()]).err().unwrap();
+		assert_eq!(Roster::open(&[builds]).unwrap().characters.len(), 15);
+		assert!(error.starts_with(&broken.display().to_string()), "{}", error);
+		assert!(Roster::open(&[dir.join("missing.json")]).is_err());
+	}
+
+	#[tokio::test]
+	async fn fetches_builds_and_calculations_together() {
+		let server = MockServer::start();
+		let slow = |json: &str| Reply::Slow(Duration::from_millis(400), Box::new(Reply::Json(json.to_string())));
+		server.reply("/api/builds/", slow(FIXTURE_BUILDS));
+		server.reply("/api/getCalculationsForUser/", slow(FIXTURE_CALCULATIONS));
+
+		let start = Instant::now();
+		let (roster, warnings) = Roster::fetch(&server.client(), FIXTURE_UID).await.unwrap();
+		assert!(start.elapsed() < Duration::from_millis(750), "took {:?}", start.elapsed());
+		assert!(warnings.is_empty());
+		assert_eq!(roster.characters.len(), 15);
+		assert_eq!(roster.calculations.as_array().map(Vec::len), Some(7));
+	}
+
+	#[tokio::test]
+	async fn builds_survive_failed_calculations() {
+		let server = MockServer::start();
+		server.reply("/api/getCalculationsForUser/", Reply::Status(503));
+		let (roster, warnings) = Roster::fetch(&server.client(), FIXTURE_UID).await.unwrap();
+		assert_eq!(roster.characters.len(), 15);
+		assert!(roster.calculations.is_null());
+		assert_eq!(warnings.len(), 1);
+		assert!(warnings[0].starts_with("Failed to fetch calculations"));
+
+		server.reply("/api/builds/", Reply::Status(500));
+		assert!(Roster::fetch(&server.client(), FIXTURE_UID).await.is_err());
+	}
+}
@@ -0,0 +1,112 @@
+use serde_json::Value;
+
+use crate::models;
+use crate::utils;
+
+#[derive(Debug)]
+pub struct OwnerProfile {
+	pub nickname: String,
+	pub adventure_rank: i64,
+	pub region: String,
+	pub profile_picture: Option<String>,
+	pub name_card: Option<String>,
+	pub patreon: bool,
+}
+
+impl OwnerProfile {
+	// Every build carries a copy of the owner, saved ones as it was back then
+	pub fn from_builds(chars: &[Value]) -> Option<Self> {
+		let char = chars
+			.iter()
+			.find(|char| models::build_type(char) == "current")
+			.or_else(|| chars.first())?;
+		let owner = char.get("owner")?;
+
+		let profile_picture = char["profilePictureLink"]
+			.as_str()
+			.or_else(|| owner["profilePicture"]["assets"]["icon"].as_str())
+			.map(utils::icon_url);
+		let name_card = char["nameCardLink"]
+			.as_str()
+			.or_else(|| owner["nameCard"].as_str())
+			.map(utils::icon_url);
+
+		Some(Self {
+			nickname: owner["nickname"].as_str().unwrap_or("Unknown").to_string(),
+			// AR is sent as a float (57.0000001667)
+			adventure_rank: owner["adventureRank"].as_f64().unwrap_or(0.0).floor() as i64,
+			region: owner["region"].as_str().unwrap_or("").to_string(),
+			profile_picture,
+			name_card,
+			patreon: owner["patreon"]["active"].as_bool().unwrap_or(false),
+		})
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Percentile {
+	pub name: String,
+	pub calculation: String,
+	pub ranking: i64,
+	pub out_of: i64,
+}
+
+impl Percentile {
+	pub fn top_percent(&self) -> f64 {
+		self.ranking as f64 / self.out_of as f64 * 100.0
+	}
+}
+
+#[derive(Debug)]
+pub struct RosterSummary {
+	pub character_count: usize,
+	// (element, count), most common first
+	pub elements: Vec<(String, usize)>,
+	pub average_crit_value: f64,
+	pub best: Option<Percentile>,
+	pub worst: Option<Percentile>,
+}
+
+impl RosterSummary {
+	pub fn new(chars: &[&Value], calculations: &[&Value]) -> Self {
+		let mut elements: Vec<(String, usize)> = Vec::new();
+		for char in chars {
+			let element = char["characterMetadata"]["element"].as_str().unwrap_or("Unknown");
+			match elements.iter_mut().find(|(name, _)| name == element) {
+				Some((_, count)) => *count += 1,
+				None => elements.push((element.to_string(), 1)),
+			}
+		}
+		elements.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+		let crit_values: Vec<f64> = chars.iter().filter_map(|c| c["critValue"].as_f64()).collect();
+		let average_crit_value = if crit_values.is_empty() {
+			0.0
+		} else {
+			crit_values.iter().sum::<f64>() / crit_values.len() as f64
+		};
+
+		let mut percentiles: Vec<Percentile> = calculations.iter().copied().filter_map(percentile).collect();
+		percentiles.sort_by(|a, b| a.top_percent().total_cmp(&b.top_percent()));
+
+		Self {
+			character_count: chars.len(),
+			elements,
+			average_crit_value,
+			best: percentiles.first().cloned(),
+			worst: percentiles.last().cloned(),
+		}
+	}
+}
+
+fn percentile(calc: &Value) -> Option<Percentile> {
+	let fit = &calc["calculations"]["fit"];
+	let ranking = fit["ranking"].as_i64()?;
+	let out_of = fit["outOf"].as_i64().filter(|total| *total > 0)?;
+	Some(Percentile {
+		name: calc["name"].as_str().unwrap_or("Unknown").to_string(),
+		calculation: fit["name"].as_str().unwrap_or("").to_string(),
+		ranking,
+		out_of,
+	})
+}
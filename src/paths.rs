@@ -0,0 +1,351 @@
+use serde_json::{json, Value};
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::history::Snapshot;
+use crate::models::{self, Roster};
+use crate::settings;
+
+const APP_DIR: &str = "genshin-viewer";
+const UID_FILE: &str = "saved_uid.txt";
+// The history tab only reads this many of the newest snapshots
+const MAX_SNAPSHOTS: usize = 50;
+
+// Config (uid, settings), cache (images, API responses) and data (history snapshots)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paths {
+	pub config: PathBuf,
+	pub cache: PathBuf,
+	pub data: PathBuf,
+	// Set when there was no home directory and something lives in the temp dir instead
+	pub temporary: bool,
+}
+
+impl Paths {
+	pub fn from_env() -> Self {
+		Self::resolve(|key| std::env::var_os(key))
+	}
+
+	pub fn resolve(env: impl Fn(&str) -> Option<OsString>) -> Self {
+		// The XDG spec says relative paths are invalid and should be ignored
+		let var = |key: &str| env(key).map(PathBuf::from).filter(|path| path.is_absolute());
+		let mut temporary = false;
+		let mut fallback = |kind: &str| {
+			temporary = true;
+			std::env::temp_dir().join(APP_DIR).join(kind)
+		};
+
+		if cfg!(windows) {
+			let local = var("LOCALAPPDATA")
+				.or_else(|| var("USERPROFILE").map(|profile| profile.join("AppData").join("Local")))
+				.map(|local| local.join(APP_DIR));
+			let config = local.clone().unwrap_or_else(|| fallback("config"));
+			let cache = local.as_ref().map(|local| local.join("cache")).unwrap_or_else(|| fallback("cache"));
+			let data = local.as_ref().map(|local| local.join("data")).unwrap_or_else(|| fallback("data"));
+			return Self { config, cache, data, temporary };
+		}
+
+		let home = var("HOME");
+		let xdg = |key: &str, default: &[&str]| {
+			var(key)
+				.or_else(|| home.as_ref().map(|home| default.iter().fold(home.clone(), |path, part| path.join(part))))
+				.map(|path| path.join(APP_DIR))
+		};
+		let config = xdg("XDG_CONFIG_HOME", &[".config"]).unwrap_or_else(|| fallback("config"));
+		let cache = xdg("XDG_CACHE_HOME", &[".cache"]).unwrap_or_else(|| fallback("cache"));
+		let data = xdg("XDG_DATA_HOME", &[".local", "share"]).unwrap_or_else(|| fallback("data"));
+		Self { config, cache, data, temporary }
+	}
+
+	// Where everything lived before, regardless of XDG_CONFIG_HOME
+	pub fn legacy_config(env: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+		if cfg!(windows) {
+			return None;
+		}
+		let home = PathBuf::from(env("HOME")?);
+		Some(home.join(".config").join(APP_DIR))
+	}
+
+	pub fn create_dirs(&self) {
+		for dir in [&self.config, &self.cache, &self.data] {
+			std::fs::create_dir_all(dir).ok();
+		}
+	}
+
+	// Moves files from the legacy config dir, returns what was moved. Files that already
+	// exist in the new location win, so running this again is harmless.
+	pub fn migrate_from(&self, legacy: &Path) -> Vec<PathBuf> {
+		if legacy == self.config {
+			return Vec::new();
+		}
+		let mut moved = Vec::new();
+		for name in [UID_FILE, settings::FILE_NAME] {
+			let from = legacy.join(name);
+			let to = self.config.join(name);
+			if !from.is_file() || to.exists() {
+				continue;
+			}
+			std::fs::create_dir_all(&self.config).ok();
+			// rename fails across filesystems, copy then
+			let result = std::fs::rename(&from, &to)
+				.or_else(|_| std::fs::copy(&from, &to).and_then(|_| std::fs::remove_file(&from)));
+			if result.is_ok() {
+				moved.push(to);
+			}
+		}
+		moved
+	}
+
+	pub fn uid_file(&self) -> PathBuf {
+		self.config.join(UID_FILE)
+	}
+
+	pub fn settings_file(&self) -> PathBuf {
+		self.config.join(settings::FILE_NAME)
+	}
+
+	pub fn image_cache_dir(&self) -> PathBuf {
+		self.cache.join("images")
+	}
+
+	fn response_file(&self, uid: &str) -> PathBuf {
+		self.cache.join("api").join(format!("{}.json", uid))
+	}
+
+	pub fn history_dir(&self, uid: &str) -> PathBuf {
+		self.data.join("history").join(uid)
+	}
+
+	// Keeps the latest response in the cache and a timestamped copy in the history.
+	// Returns the snapshot, unless nothing changed since the last response.
+	pub fn store_response(&self, uid: &str, roster: &Roster) -> io::Result<Option<PathBuf>> {
+		let json = serde_json::to_string(&roster.to_json())?;
+
+		let response = self.response_file(uid);
+		if std::fs::read_to_string(&response).ok().as_deref() == Some(json.as_str()) {
+			return Ok(None);
+		}
+		std::fs::create_dir_all(response.parent().unwrap_or(&self.cache))?;
+		std::fs::write(&response, &json)?;
+
+		let history = self.history_dir(uid);
+		std::fs::create_dir_all(&history)?;
+		// "<seconds>.json", then "<seconds>-1.json" and so on within the same second
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+		let snapshot = (0..)
+			.map(|n| match n {
+				0 => history.join(format!("{}.json", now)),
+				n => history.join(format!("{}-{}.json", now, n)),
+			})
+			.find(|file| !file.exists())
+			.unwrap_or_default();
+		std::fs::write(&snapshot, &json)?;
+		Ok(Some(snapshot))
+	}
+
+	// Oldest first. Unreadable files are skipped.
+	pub fn load_history(&self, uid: &str) -> Vec<Snapshot> {
+		let Ok(entries) = std::fs::read_dir(self.history_dir(uid)) else {
+			return Vec::new();
+		};
+		let mut files: Vec<(u64, u64, PathBuf)> = entries
+			.filter_map(|entry| {
+				let path = entry.ok()?.path();
+				let stem = path.file_stem()?.to_str()?;
+				let (time, n) = stem.split_once('-').unwrap_or((stem, "0"));
+				Some((time.parse().ok()?, n.parse().ok()?, path))
+			})
+			.collect();
+		files.sort();
+
+		let skip = files.len().saturating_sub(MAX_SNAPSHOTS);
+		files
+			.into_iter()
+			.skip(skip)
+			.filter_map(|(time, _, path)| {
+				let json = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+				Some(Snapshot {
+					time,
+					roster: Roster::from_json(&json)?,
+				})
+			})
+			.collect()
+	}
+
+	// The selected build with its calculation, or the whole roster
+	pub fn export(&self, uid: &str, roster: &Roster, selected: Option<&Value>) -> io::Result<PathBuf> {
+		let (name, json) = match selected {
+			// Saved builds get their own file next to the current one
+			Some(char) => (
+				match models::build_type(char) {
+					"current" => char["name"].as_str().unwrap_or("character").to_string(),
+					build_type => format!("{}-{}", char["name"].as_str().unwrap_or("character"), build_type),
+				},
+				json!({
+					"build": char,
+					"calculation": roster.calculation_for(char),
+				}),
+			),
+			None => ("all".to_string(), roster.to_json()),
+		};
+		let name: String = name
+			.chars()
+			.map(|c| if c.is_alphanumeric() { c } else { '-' })
+			.collect();
+
+		let dir = self.data.join("exports");
+		std::fs::create_dir_all(&dir)?;
+		let file = dir.join(format!("{}-{}.json", uid, name));
+		std::fs::write(&file, serde_json::to_string_pretty(&json)?)?;
+		Ok(file)
+	}
+
+	pub fn cached_response(&self, uid: &str) -> Option<Roster> {
+		let text = std::fs::read_to_string(self.response_file(uid)).ok()?;
+		Roster::from_json(&serde_json::from_str::<Value>(&text).ok()?)
+	}
+}
+
+// Stable file name for a URL (FNV-1a), `DefaultHasher` may change between Rust releases
+pub fn cache_key(url: &str) -> String {
+	let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x100000001b3)
+	});
+	format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::{fixture_roster, temp_dir, variant_roster, FIXTURE_UID};
+	use std::collections::HashMap;
+
+	fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+		let vars: HashMap<String, OsString> = vars.iter().map(|(k, v)| (k.to_string(), OsString::from(v))).collect();
+		move |key| vars.get(key).cloned()
+	}
+
+	fn paths_in(root: &Path) -> Paths {
+		Paths {
+			config: root.join("config"),
+			cache: root.join("cache"),
+			data: root.join("data"),
+			temporary: false,
+		}
+	}
+
+	#[cfg(not(windows))]
+	#[test]
+	fn uses_home_by_default() {
+		let paths = Paths::resolve(env(&[("HOME", "/home/traveler")]));
+		assert_eq!(paths.config, PathBuf::from("/home/traveler/.config/genshin-viewer"));
+		assert_eq!(paths.cache, PathBuf::from("/home/traveler/.cache/genshin-viewer"));
+		assert_eq!(paths.data, PathBuf::from("/home/traveler/.local/share/genshin-viewer"));
+		assert!(!paths.temporary);
+	}
+
+	#[cfg(not(windows))]
+	#[test]
+	fn honours_xdg_variables() {
+		let paths = Paths::resolve(env(&[
+			("HOME", "/home/traveler"),
+			("XDG_CONFIG_HOME", "/xdg/config"),
+			("XDG_CACHE_HOME", "relative/cache"),
+			("XDG_DATA_HOME", "/xdg/data"),
+		]));
+		assert_eq!(paths.config, PathBuf::from("/xdg/config/genshin-viewer"));
+		// Relative values are ignored
+		assert_eq!(paths.cache, PathBuf::from("/home/traveler/.cache/genshin-viewer"));
+		assert_eq!(paths.data, PathBuf::from("/xdg/data/genshin-viewer"));
+	}
+
+	#[cfg(not(windows))]
+	#[test]
+	fn missing_home_falls_back_to_temp() {
+		let paths = Paths::resolve(env(&[("XDG_CONFIG_HOME", "/xdg/config")]));
+		assert!(paths.temporary);
+		assert_eq!(paths.config, PathBuf::from("/xdg/config/genshin-viewer"));
+		assert!(paths.cache.starts_with(std::env::temp_dir()));
+		assert!(paths.data.starts_with(std::env::temp_dir()));
+		assert_eq!(Paths::legacy_config(env(&[])), None);
+	}
+
+	#[test]
+	fn migrates_saved_uid_once() {
+		let paths = paths_in(&temp_dir("migrate"));
+		let legacy = paths.config.with_file_name("legacy");
+		std::fs::create_dir_all(&legacy).unwrap();
+		std::fs::write(legacy.join(UID_FILE), FIXTURE_UID).unwrap();
+
+		assert_eq!(paths.migrate_from(&legacy), vec![paths.uid_file()]);
+		assert_eq!(std::fs::read_to_string(paths.uid_file()).unwrap(), FIXTURE_UID);
+		assert!(!legacy.join(UID_FILE).exists());
+
+		// A stale legacy file never overwrites the current one
+		std::fs::write(legacy.join(UID_FILE), "800000001").unwrap();
+		assert!(paths.migrate_from(&legacy).is_empty());
+		assert_eq!(std::fs::read_to_string(paths.uid_file()).unwrap(), FIXTURE_UID);
+	}
+
+	#[test]
+	fn stores_responses_and_history() {
+		let paths = paths_in(&temp_dir("store"));
+		assert!(paths.cached_response(FIXTURE_UID).is_none());
+
+		let snapshot = paths.store_response(FIXTURE_UID, &fixture_roster()).unwrap().unwrap();
+		assert!(snapshot.starts_with(paths.history_dir(FIXTURE_UID)));
+		let cached = paths.cached_response(FIXTURE_UID).unwrap();
+		assert_eq!(cached.characters.len(), 15);
+		assert_eq!(cached.calculations, fixture_roster().calculations);
+
+		// The same response again is not another snapshot
+		assert_eq!(paths.store_response(FIXTURE_UID, &fixture_roster()).unwrap(), None);
+		std::fs::write(paths.history_dir(FIXTURE_UID).join("notes.txt"), "not a snapshot").unwrap();
+		let history = paths.load_history(FIXTURE_UID);
+		assert_eq!(history.len(), 1);
+		assert_eq!(history[0].roster.characters.len(), 15);
+
+		// A different one within the same second gets a snapshot of its own, after the first
+		let mut changed = fixture_roster();
+		changed.characters.pop();
+		let second = paths.store_response(FIXTURE_UID, &changed).unwrap().unwrap();
+		assert_ne!(second, snapshot);
+		let history = paths.load_history(FIXTURE_UID);
+		assert_eq!(history.len(), 2);
+		assert_eq!(history[1].roster.characters.len(), 14);
+		assert!(paths.load_history("800000001").is_empty());
+	}
+
+	#[test]
+	fn exports_builds() {
+		let paths = paths_in(&temp_dir("export"));
+		let roster = fixture_roster();
+		let idx = roster.characters.iter().position(|c| c["name"] == "Furina").unwrap();
+
+		let file = paths.export(FIXTURE_UID, &roster, roster.characters.get(idx)).unwrap();
+		assert!(file.ends_with(format!("{}-Furina.json", FIXTURE_UID)));
+		let json: Value = serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+		assert_eq!(json["build"]["name"], "Furina");
+		assert_eq!(json["calculation"]["characterId"], roster.characters[idx]["characterId"]);
+
+		let file = paths.export(FIXTURE_UID, &roster, None).unwrap();
+		let json: Value = serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+		assert_eq!(json["builds"].as_array().unwrap().len(), 15);
+
+		// A saved build does not overwrite the current one
+		let roster = variant_roster("Furina", "Saved Build");
+		let file = paths.export(FIXTURE_UID, &roster, roster.characters.last()).unwrap();
+		assert!(file.ends_with(format!("{}-Furina-Saved-Build.json", FIXTURE_UID)));
+		let json: Value = serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+		assert_eq!(json["build"]["type"], "Saved Build");
+		assert_eq!(json["calculation"]["type"], "Saved Build");
+	}
+
+	#[test]
+	fn cache_keys_are_stable() {
+		assert_eq!(cache_key(""), "cbf29ce484222325");
+		assert_ne!(cache_key("https://enka.network/ui/a.png"), cache_key("https://enka.network/ui/b.png"));
+	}
+}
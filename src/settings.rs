@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::models::SortOrder;
+use crate::stats::StatKind;
+pub use crate::theme::Theme;
+use crate::utils::NumberLocale;
+
+pub const FILE_NAME: &str = "settings.json";
+
+// (code, name). Only number formatting follows the language for now.
+pub const LANGUAGES: &[(&str, &str)] = &[
+	("en", "English"),
+	("de", "Deutsch"),
+	("de-CH", "Deutsch (Schweiz)"),
+	("fr", "Français"),
+];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+	pub theme: Theme,
+	// Tint selections and headings with the selected character's element
+	pub element_accent: bool,
+	pub ui_scale: f32,
+	pub language: String,
+	pub cache_limit_mb: u32,
+	// None uses akasha.cv
+	pub api_base_url: Option<String>,
+	// Minutes, 0 turns it off
+	pub auto_refresh_minutes: u32,
+	pub sort: SortOrder,
+	// Stat names, so stats added later are shown by default
+	pub hidden_stats: Vec<String>,
+	// Keys written by newer versions, kept so saving doesn't drop them
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			theme: Theme::Dark,
+			element_accent: false,
+			ui_scale: 1.0,
+			language: "en".to_string(),
+			cache_limit_mb: 256,
+			api_base_url: None,
+			auto_refresh_minutes: 0,
+			sort: SortOrder::CritValue,
+			hidden_stats: Vec::new(),
+			extra: Map::new(),
+		}
+	}
+}
+
+impl Settings {
+	// Never fails: unreadable files give the defaults and a value this version doesn't
+	// understand (e.g. a theme added later) only resets that one setting
+	pub fn from_json(text: &str) -> Self {
+		let Ok(Value::Object(file)) = serde_json::from_str::<Value>(text) else {
+			return Self::default();
+		};
+		let Ok(Value::Object(mut merged)) = serde_json::to_value(Self::default()) else {
+			return Self::default();
+		};
+		for (key, value) in file {
+			let mut candidate = merged.clone();
+			candidate.insert(key.clone(), value.clone());
+			if serde_json::from_value::<Self>(Value::Object(candidate)).is_ok() {
+				merged.insert(key, value);
+			}
+		}
+		serde_json::from_value::<Self>(Value::Object(merged))
+			.unwrap_or_default()
+			.sanitized()
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).unwrap_or_default()
+	}
+
+	fn sanitized(mut self) -> Self {
+		if !(0.5..=3.0).contains(&self.ui_scale) {
+			self.ui_scale = 1.0;
+		}
+		if self.api_base_url.as_deref().map(str::trim) == Some("") {
+			self.api_base_url = None;
+		}
+		self
+	}
+
+	pub fn number_locale(&self) -> NumberLocale {
+		match self.language.as_str() {
+			"de-CH" => NumberLocale::Swiss,
+			"de" => NumberLocale::German,
+			"fr" => NumberLocale::French,
+			_ => NumberLocale::English,
+		}
+	}
+
+	pub fn shows_stat(&self, kind: StatKind) -> bool {
+		!self.hidden_stats.iter().any(|name| name == kind.name())
+	}
+
+	pub fn set_stat_shown(&mut self, kind: StatKind, shown: bool) {
+		self.hidden_stats.retain(|name| name != kind.name());
+		if !shown {
+			self.hidden_stats.push(kind.name().to_string());
+		}
+	}
+
+	pub fn cache_limit_bytes(&self) -> usize {
+		self.cache_limit_mb as usize * 1024 * 1024
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn missing_or_broken_files_use_defaults() {
+		assert_eq!(Settings::from_json(""), Settings::default());
+		assert_eq!(Settings::from_json("{ not json"), Settings::default());
+		assert_eq!(Settings::from_json("[1, 2]"), Settings::default());
+	}
+
+	#[test]
+	fn older_files_fill_in_new_fields() {
+		let settings = Settings::from_json(r#"{ "theme": "HighContrast" }"#);
+		assert_eq!(settings.theme, Theme::HighContrast);
+		assert!(!settings.element_accent);
+		assert_eq!(settings.ui_scale, 1.0);
+		assert_eq!(settings.sort, SortOrder::CritValue);
+	}
+
+	#[test]
+	fn unknown_keys_survive_a_save() {
+		let settings = Settings::from_json(r#"{ "ui_scale": 1.5, "future_option": { "x": 1 } }"#);
+		assert_eq!(settings.ui_scale, 1.5);
+
+		let saved: Value = serde_json::from_str(&settings.to_json()).unwrap();
+		assert_eq!(saved["future_option"]["x"], 1);
+		assert_eq!(Settings::from_json(&settings.to_json()), settings);
+	}
+
+	#[test]
+	fn values_from_newer_versions_only_reset_that_setting() {
+		let settings = Settings::from_json(r#"{ "theme": "Sepia", "language": "de", "ui_scale": 40 }"#);
+		assert_eq!(settings.theme, Theme::Dark);
+		assert_eq!(settings.language, "de");
+		assert_eq!(settings.number_locale(), NumberLocale::German);
+		assert_eq!(settings.ui_scale, 1.0);
+	}
+
+	#[test]
+	fn hiding_stats() {
+		let mut settings = Settings::default();
+		assert!(settings.shows_stat(StatKind::CritRate));
+		settings.set_stat_shown(StatKind::CritRate, false);
+		settings.set_stat_shown(StatKind::CritRate, false);
+		assert!(!settings.shows_stat(StatKind::CritRate));
+		assert_eq!(settings.hidden_stats.len(), 1);
+		settings.set_stat_shown(StatKind::CritRate, true);
+		assert!(settings.shows_stat(StatKind::CritRate));
+	}
+}
@@ -0,0 +1,1170 @@
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::history::Snapshot;
+use crate::models::Roster;
+use crate::settings::Settings;
+use crate::uid;
+use crate::view::{CharacterView, RosterView};
+
+// Identifies a fetch so results that arrive after the user moved on can be dropped
+pub type RequestId = u64;
+
+pub enum Phase {
+	NoUid {
+		input: String,
+		error: Option<String>,
+	},
+	Loading {
+		uid: String,
+		request: RequestId,
+		// A confirmed UID is already saved and has loaded before
+		confirmed: bool,
+	},
+	Loaded {
+		uid: String,
+		roster: Roster,
+	},
+	Error {
+		uid: String,
+		message: String,
+	},
+	Refreshing {
+		uid: String,
+		roster: Roster,
+		request: RequestId,
+	},
+	// Saved JSON from disk, never refreshed
+	Opened {
+		files: Vec<PathBuf>,
+		roster: Roster,
+	},
+}
+
+// File → Open, one path per line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OpenDialog {
+	pub input: String,
+	pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DetailTab {
+	#[default]
+	Overview,
+	Artifacts,
+	Weapon,
+	Talents,
+	Calculations,
+	Compare,
+	History,
+	RawData,
+}
+
+impl DetailTab {
+	pub const ALL: [DetailTab; 8] = [
+		DetailTab::Overview,
+		DetailTab::Artifacts,
+		DetailTab::Weapon,
+		DetailTab::Talents,
+		DetailTab::Calculations,
+		DetailTab::Compare,
+		DetailTab::History,
+		DetailTab::RawData,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			DetailTab::Overview => "Overview",
+			DetailTab::Artifacts => "Artifacts",
+			DetailTab::Weapon => "Weapon",
+			DetailTab::Talents => "Talents & Constellations",
+			DetailTab::Calculations => "Leaderboards",
+			DetailTab::Compare => "Compare Builds",
+			DetailTab::History => "History",
+			DetailTab::RawData => "Raw Data",
+		}
+	}
+}
+
+// Calculations are fetched next to the builds and may arrive after them, or fail
+// while the builds still show
+#[derive(Clone, Debug, PartialEq)]
+pub enum CalculationStatus {
+	Done,
+	Loading(RequestId),
+	Failed(String),
+}
+
+pub enum Event {
+	UidInput(String),
+	SubmitUid,
+	Select(Option<usize>),
+	Search(String),
+	// Filters the raw data tab
+	SearchRaw(String),
+	// Moves the keyboard cursor through the visible list, Enter opens it
+	MoveCursor(i32),
+	OpenCursor,
+	Refresh,
+	Logout,
+	Export,
+	ShowOpen(bool),
+	OpenInput(String),
+	SubmitOpen,
+	// Dropped on the window or given with --from-file
+	OpenFiles(Vec<PathBuf>),
+	CloseFile,
+	ShowSettings(bool),
+	ShowHelp(bool),
+	ShowTab(DetailTab),
+	// Esc: closes the topmost overlay
+	CloseOverlay,
+	Notice(String),
+	UpdateSettings(Settings),
+	// The builds, calculations follow as `CalculationsFetched` with the same request
+	Fetched {
+		request: RequestId,
+		result: Result<Roster, String>,
+	},
+	CalculationsFetched {
+		request: RequestId,
+		result: Result<Value, String>,
+	},
+	RetryCalculations,
+	HistoryLoaded {
+		uid: String,
+		snapshots: Vec<Snapshot>,
+	},
+	Opened {
+		files: Vec<PathBuf>,
+		result: Result<Roster, String>,
+	},
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Effect {
+	Fetch { uid: String, request: RequestId },
+	FetchCalculations { uid: String, request: RequestId },
+	// Cache the complete roster and add a history snapshot
+	StoreResponse { uid: String },
+	SaveUid(String),
+	ForgetUid,
+	// Write the selected build, or the whole roster without a selection
+	Export { uid: String, selected: Option<usize> },
+	// Read the stored snapshots back as `Event::HistoryLoaded`
+	LoadHistory { uid: String },
+	// Read saved JSON back as `Event::Opened`
+	OpenFiles(Vec<PathBuf>),
+	// Write `AppState::settings` to disk and apply them
+	SaveSettings,
+}
+
+pub struct AppState {
+	pub phase: Phase,
+	pub selected_character: Option<usize>,
+	pub list_cursor: Option<usize>,
+	pub search: String,
+	pub settings: Settings,
+	pub settings_open: bool,
+	pub help_open: bool,
+	pub open_dialog: Option<OpenDialog>,
+	// Kept when switching characters
+	pub detail_tab: DetailTab,
+	// Snapshots of the current UID, loaded the first time the history tab is shown
+	pub history: Option<Vec<Snapshot>>,
+	pub raw_search: String,
+	// One line of feedback, e.g. where an export went
+	pub notice: Option<String>,
+	pub calculations: CalculationStatus,
+	// Rebuilt whenever the roster changes, so frames don't walk the JSON
+	view: Option<RosterView>,
+	// What File → Open replaced, shown again once the file is closed
+	account: Option<Phase>,
+	next_request: RequestId,
+}
+
+impl AppState {
+	// A saved UID was confirmed by an earlier session, so it is loaded right away
+	pub fn start(saved_uid: Option<String>, settings: Settings) -> (Self, Vec<Effect>) {
+		let mut state = Self {
+			phase: Phase::NoUid {
+				input: String::new(),
+				error: None,
+			},
+			selected_character: None,
+			list_cursor: None,
+			search: String::new(),
+			settings,
+			settings_open: false,
+			help_open: false,
+			open_dialog: None,
+			detail_tab: DetailTab::Overview,
+			history: None,
+			raw_search: String::new(),
+			notice: None,
+			calculations: CalculationStatus::Done,
+			view: None,
+			account: None,
+			next_request: 0,
+		};
+		let effects = match saved_uid {
+			Some(uid) => vec![state.load(uid, true)],
+			None => Vec::new(),
+		};
+		(state, effects)
+	}
+
+	// Shows the last response from disk while the saved UID loads, the fetch then
+	// finishes like a refresh
+	pub fn show_cached(&mut self, cached: Roster) {
+		let phase = std::mem::replace(
+			&mut self.phase,
+			Phase::NoUid {
+				input: String::new(),
+				error: None,
+			},
+		);
+		self.phase = match phase {
+			Phase::Loading {
+				uid,
+				request,
+				confirmed: true,
+			} => Phase::Refreshing {
+				uid,
+				roster: cached,
+				request,
+			},
+			phase => phase,
+		};
+		self.rebuild_view();
+	}
+
+	pub fn uid(&self) -> Option<&str> {
+		match &self.phase {
+			Phase::NoUid { .. } | Phase::Opened { .. } => None,
+			Phase::Loading { uid, .. }
+			| Phase::Loaded { uid, .. }
+			| Phase::Error { uid, .. }
+			| Phase::Refreshing { uid, .. } => Some(uid),
+		}
+	}
+
+	pub fn roster(&self) -> Option<&Roster> {
+		match &self.phase {
+			Phase::Loaded { roster, .. } | Phase::Refreshing { roster, .. } | Phase::Opened { roster, .. } => Some(roster),
+			_ => None,
+		}
+	}
+
+	fn roster_mut(&mut self) -> Option<&mut Roster> {
+		match &mut self.phase {
+			Phase::Loaded { roster, .. } | Phase::Refreshing { roster, .. } => Some(roster),
+			_ => None,
+		}
+	}
+
+	// Calculation-only characters have a stand-in build, see `RosterView::build`
+	pub fn selected(&self) -> Option<&Value> {
+		self.view()?.build(self.roster()?, self.selected_character?)
+	}
+
+	// Present exactly when `roster` is
+	pub fn view(&self) -> Option<&RosterView> {
+		self.view.as_ref()
+	}
+
+	pub fn selected_view(&self) -> Option<&CharacterView> {
+		self.view()?.characters.get(self.selected_character?)
+	}
+
+	pub fn selected_calculation(&self) -> Option<&Value> {
+		self.view()?.calculation(self.roster()?, self.selected_character?)
+	}
+
+	// Indices of the characters in the list, in sort order and matching the search
+	pub fn visible_characters(&self) -> Vec<usize> {
+		let Some(view) = self.view() else {
+			return Vec::new();
+		};
+		let search = self.search.trim().to_lowercase();
+		view.sorted(self.settings.sort)
+			.iter()
+			.copied()
+			.filter(|idx| view.matches(*idx, &search))
+			.collect()
+	}
+
+	pub fn handle(&mut self, event: Event) -> Vec<Effect> {
+		match event {
+			Event::UidInput(text) => {
+				if let Phase::NoUid { input, error } = &mut self.phase {
+					*input = text;
+					*error = None;
+				}
+				Vec::new()
+			}
+			Event::SubmitUid => {
+				let Phase::NoUid { input, error } = &mut self.phase else {
+					return Vec::new();
+				};
+				match uid::validate(input) {
+					Ok(_) => {
+						let uid = input.trim().to_string();
+						vec![self.load(uid, false)]
+					}
+					Err(e) => {
+						*error = Some(e.to_string());
+						Vec::new()
+					}
+				}
+			}
+			Event::Select(selected) => {
+				let count = self.view().map(|view| view.characters.len()).unwrap_or(0);
+				self.selected_character = selected.filter(|idx| *idx < count);
+				// Saved variants are not listed, the cursor stays on their character
+				if let Some(selected) = self.selected_character {
+					self.list_cursor = self.view().map(|view| view.primary(selected));
+				}
+				Vec::new()
+			}
+			Event::Search(text) => {
+				self.search = text;
+				let visible = self.visible_characters();
+				self.list_cursor = self.list_cursor.filter(|idx| visible.contains(idx));
+				Vec::new()
+			}
+			Event::SearchRaw(text) => {
+				self.raw_search = text;
+				Vec::new()
+			}
+			Event::MoveCursor(delta) => {
+				let visible = self.visible_characters();
+				let Some(last) = visible.len().checked_sub(1) else {
+					return Vec::new();
+				};
+				let position = self.list_cursor.and_then(|cursor| visible.iter().position(|idx| *idx == cursor));
+				let position = match position {
+					Some(position) => (position as i64 + delta as i64).clamp(0, last as i64) as usize,
+					None if delta < 0 => last,
+					None => 0,
+				};
+				self.list_cursor = Some(visible[position]);
+				Vec::new()
+			}
+			Event::OpenCursor => match self.list_cursor {
+				Some(cursor) => self.handle(Event::Select(Some(cursor))),
+				None => Vec::new(),
+			},
+			Event::Refresh => self.refresh(),
+			Event::Logout => {
+				self.close();
+				vec![Effect::ForgetUid]
+			}
+			Event::Export => match (self.uid(), self.roster()) {
+				(Some(uid), Some(_)) => vec![Effect::Export {
+					uid: uid.to_string(),
+					selected: self.selected_character,
+				}],
+				_ => Vec::new(),
+			},
+			Event::ShowSettings(open) => {
+				self.settings_open = open;
+				Vec::new()
+			}
+			Event::ShowHelp(open) => {
+				self.help_open = open;
+				Vec::new()
+			}
+			Event::ShowOpen(open) => {
+				if !open {
+					self.open_dialog = None;
+				} else if self.open_dialog.is_none() {
+					self.open_dialog = Some(OpenDialog::default());
+				}
+				Vec::new()
+			}
+			Event::OpenInput(text) => {
+				if let Some(dialog) = &mut self.open_dialog {
+					dialog.input = text;
+					dialog.error = None;
+				}
+				Vec::new()
+			}
+			Event::SubmitOpen => {
+				let Some(dialog) = &mut self.open_dialog else {
+					return Vec::new();
+				};
+				// Pasted paths often come quoted
+				let files: Vec<PathBuf> = dialog
+					.input
+					.lines()
+					.map(|line| line.trim().trim_matches('"'))
+					.filter(|line| !line.is_empty())
+					.map(PathBuf::from)
+					.collect();
+				if files.is_empty() {
+					dialog.error = Some("Enter the path of a saved builds or calculations file".to_string());
+					return Vec::new();
+				}
+				vec![Effect::OpenFiles(files)]
+			}
+			Event::OpenFiles(files) => {
+				if files.is_empty() {
+					return Vec::new();
+				}
+				vec![Effect::OpenFiles(files)]
+			}
+			Event::CloseFile => {
+				if !matches!(self.phase, Phase::Opened { .. }) {
+					return Vec::new();
+				}
+				let account = self.account.take();
+				self.close();
+				self.restore(account)
+			}
+			Event::ShowTab(tab) => {
+				self.detail_tab = tab;
+				self.load_history()
+			}
+			Event::CloseOverlay => {
+				if self.help_open {
+					self.help_open = false;
+				} else if self.open_dialog.is_some() {
+					self.open_dialog = None;
+				} else if self.settings_open {
+					self.settings_open = false;
+				} else {
+					self.notice = None;
+				}
+				Vec::new()
+			}
+			Event::Notice(notice) => {
+				self.notice = Some(notice);
+				Vec::new()
+			}
+			Event::UpdateSettings(settings) => {
+				if settings == self.settings {
+					return Vec::new();
+				}
+				self.settings = settings;
+				vec![Effect::SaveSettings]
+			}
+			Event::Fetched { request, result } => {
+				let mut effects = self.fetched(request, result);
+				effects.extend(self.load_history());
+				effects
+			}
+			Event::CalculationsFetched { request, result } => {
+				if self.calculations != CalculationStatus::Loading(request) {
+					return Vec::new();
+				}
+				let (Some(uid), Some(roster)) = (self.uid().map(str::to_string), self.roster_mut()) else {
+					return Vec::new();
+				};
+				// The builds are stored either way, without calculations when those failed.
+				// Older ones would not match the new builds
+				self.calculations = match result {
+					Ok(calculations) => {
+						roster.calculations = calculations;
+						CalculationStatus::Done
+					}
+					Err(error) => {
+						roster.calculations = Value::Null;
+						CalculationStatus::Failed(error)
+					}
+				};
+				self.rebuild_view();
+				self.history = None;
+				let mut effects = vec![Effect::StoreResponse { uid }];
+				effects.extend(self.load_history());
+				effects
+			}
+			Event::RetryCalculations => match (&self.calculations, &self.phase) {
+				(CalculationStatus::Failed(_), Phase::Loaded { uid, .. } | Phase::Refreshing { uid, .. }) => {
+					let uid = uid.clone();
+					let request = self.next_request();
+					self.calculations = CalculationStatus::Loading(request);
+					vec![Effect::FetchCalculations { uid, request }]
+				}
+				_ => Vec::new(),
+			},
+			Event::HistoryLoaded { uid, snapshots } => {
+				if self.uid() == Some(uid.as_str()) {
+					self.history = Some(snapshots);
+				}
+				Vec::new()
+			}
+			Event::Opened { files, result } => {
+				match result {
+					Ok(roster) => {
+						// Replaces whatever was shown, a fetch still in flight is ignored
+						let previous = std::mem::replace(
+							&mut self.phase,
+							Phase::NoUid {
+								input: String::new(),
+								error: None,
+							},
+						);
+						let account = match previous {
+							Phase::NoUid { .. } => None,
+							Phase::Opened { .. } => self.account.take(),
+							phase => Some(phase),
+						};
+						self.close();
+						self.account = account;
+						self.phase = Phase::Opened { files, roster };
+						self.rebuild_view();
+					}
+					Err(error) => {
+						// Dropped files have no dialog yet, this one shows what went wrong
+						let input = files.iter().map(|file| file.display().to_string()).collect::<Vec<_>>().join("\n");
+						self.open_dialog = Some(OpenDialog {
+							input,
+							error: Some(error),
+						});
+					}
+				}
+				Vec::new()
+			}
+		}
+	}
+
+	// After the roster changed. Calculations can add or drop characters, so the
+	// selection is bounded again.
+	fn rebuild_view(&mut self) {
+		self.view = self.roster().map(RosterView::new);
+		let count = self.view().map(|view| view.characters.len()).unwrap_or(0);
+		self.selected_character = self.selected_character.filter(|idx| *idx < count);
+		self.list_cursor = self.list_cursor.filter(|idx| *idx < count);
+	}
+
+	// Back to the UID prompt, any fetch still in flight no longer matches a phase
+	fn close(&mut self) {
+		self.phase = Phase::NoUid {
+			input: String::new(),
+			error: None,
+		};
+		self.selected_character = None;
+		self.list_cursor = None;
+		self.search.clear();
+		self.raw_search.clear();
+		self.history = None;
+		self.open_dialog = None;
+		self.notice = None;
+		self.calculations = CalculationStatus::Done;
+		self.view = None;
+		self.account = None;
+	}
+
+	// Back to the account File → Open replaced. Its fetch was ignored meanwhile, so
+	// one that was running starts over
+	fn restore(&mut self, account: Option<Phase>) -> Vec<Effect> {
+		match account {
+			Some(Phase::Loading { uid, confirmed, .. }) => vec![self.load(uid, confirmed)],
+			Some(Phase::Refreshing { uid, roster, .. }) => {
+				self.phase = Phase::Loaded { uid, roster };
+				self.rebuild_view();
+				self.refresh()
+			}
+			Some(phase) => {
+				self.phase = phase;
+				self.rebuild_view();
+				self.load_history()
+			}
+			None => Vec::new(),
+		}
+	}
+
+	// Only while the history tab is open and nothing is loaded yet
+	fn load_history(&self) -> Vec<Effect> {
+		match (self.detail_tab, &self.history, &self.phase) {
+			(DetailTab::History, None, Phase::Loaded { uid, .. } | Phase::Refreshing { uid, .. }) => {
+				vec![Effect::LoadHistory { uid: uid.clone() }]
+			}
+			_ => Vec::new(),
+		}
+	}
+
+	fn load(&mut self, uid: String, confirmed: bool) -> Effect {
+		let request = self.next_request();
+		self.phase = Phase::Loading {
+			uid: uid.clone(),
+			request,
+			confirmed,
+		};
+		self.selected_character = None;
+		self.list_cursor = None;
+		self.history = None;
+		self.calculations = CalculationStatus::Done;
+		self.view = None;
+		Effect::Fetch { uid, request }
+	}
+
+	fn next_request(&mut self) -> RequestId {
+		self.next_request += 1;
+		self.next_request
+	}
+
+	fn refresh(&mut self) -> Vec<Effect> {
+		let phase = std::mem::replace(
+			&mut self.phase,
+			Phase::NoUid {
+				input: String::new(),
+				error: None,
+			},
+		);
+		match phase {
+			Phase::Loaded { uid, roster } => {
+				let request = self.next_request();
+				self.phase = Phase::Refreshing {
+					uid: uid.clone(),
+					roster,
+					request,
+				};
+				vec![Effect::Fetch { uid, request }]
+			}
+			Phase::Error { uid, .. } => vec![self.load(uid, true)],
+			// Already fetching, or nothing to fetch
+			phase => {
+				self.phase = phase;
+				Vec::new()
+			}
+		}
+	}
+
+	fn fetched(&mut self, request: RequestId, result: Result<Roster, String>) -> Vec<Effect> {
+		let current = match &self.phase {
+			Phase::Loading { request, .. } | Phase::Refreshing { request, .. } => Some(*request),
+			_ => None,
+		};
+		if current != Some(request) {
+			return Vec::new();
+		}
+
+		let phase = std::mem::replace(
+			&mut self.phase,
+			Phase::NoUid {
+				input: String::new(),
+				error: None,
+			},
+		);
+		self.calculations = match &result {
+			// A new response may have added a snapshot
+			Ok(_) => {
+				self.history = None;
+				CalculationStatus::Loading(request)
+			}
+			Err(_) => CalculationStatus::Done,
+		};
+		let effects = match (phase, result) {
+			(Phase::Loading { uid, confirmed, .. }, Ok(roster)) => {
+				self.phase = Phase::Loaded {
+					uid: uid.clone(),
+					roster,
+				};
+				// Only confirmed UIDs are written to disk, i.e. after their first successful load
+				if confirmed {
+					Vec::new()
+				} else {
+					vec![Effect::SaveUid(uid)]
+				}
+			}
+			(Phase::Loading { uid, confirmed: false, .. }, Err(message)) => {
+				// Never got data for this UID, send the user back to the dialog
+				self.phase = Phase::NoUid {
+					error: Some(format!("Could not load UID {}: {}", uid, message)),
+					input: uid,
+				};
+				Vec::new()
+			}
+			(Phase::Refreshing { uid, roster: old, .. }, Ok(mut roster)) => {
+				// The previous calculations stay until the new ones arrive
+				if roster.calculations.is_null() {
+					roster.calculations = old.calculations;
+				}
+				self.phase = Phase::Loaded { uid, roster };
+				Vec::new()
+			}
+			(Phase::Loading { uid, .. } | Phase::Refreshing { uid, .. }, Err(message)) => {
+				self.selected_character = None;
+				self.list_cursor = None;
+				self.phase = Phase::Error { uid, message };
+				Vec::new()
+			}
+			_ => unreachable!("only in-flight phases have a request id"),
+		};
+		self.rebuild_view();
+		effects
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::SortOrder;
+	use crate::test_support::{fixture_roster, variant_roster, FIXTURE_UID};
+
+	fn fetch_request(effects: &[Effect]) -> RequestId {
+		match effects {
+			[Effect::Fetch { request, .. }] => *request,
+			_ => panic!("expected a single fetch, got {:?}", effects),
+		}
+	}
+
+	fn loaded() -> AppState {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		state
+	}
+
+	#[test]
+	fn starts_without_uid() {
+		let (state, effects) = AppState::start(None, Settings::default());
+		assert!(matches!(state.phase, Phase::NoUid { .. }));
+		assert!(effects.is_empty());
+	}
+
+	#[test]
+	fn saved_uid_loads_without_saving_again() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		assert!(matches!(state.phase, Phase::Loading { confirmed: true, .. }));
+
+		let effects = state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		assert!(effects.is_empty());
+		assert_eq!(state.roster().unwrap().characters.len(), 15);
+	}
+
+	#[test]
+	fn cached_roster_shows_while_loading() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		state.show_cached(fixture_roster());
+		assert!(matches!(state.phase, Phase::Refreshing { .. }));
+		assert_eq!(state.roster().unwrap().characters.len(), 15);
+
+		let effects = state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		assert!(effects.is_empty());
+		assert!(matches!(state.phase, Phase::Loaded { .. }));
+
+		// Only a confirmed UID that is still loading takes a cached roster
+		let (mut state, _) = AppState::start(None, Settings::default());
+		state.show_cached(fixture_roster());
+		assert!(state.roster().is_none());
+	}
+
+	#[test]
+	fn invalid_uid_stays_in_dialog() {
+		let (mut state, _) = AppState::start(None, Settings::default());
+		state.handle(Event::UidInput("12ab".to_string()));
+		assert!(state.handle(Event::SubmitUid).is_empty());
+		assert!(matches!(&state.phase, Phase::NoUid { error: Some(_), .. }));
+
+		state.handle(Event::UidInput("1".to_string()));
+		assert!(matches!(&state.phase, Phase::NoUid { error: None, .. }));
+	}
+
+	#[test]
+	fn new_uid_is_saved_after_first_load() {
+		let (mut state, _) = AppState::start(None, Settings::default());
+		state.handle(Event::UidInput(format!(" {} ", FIXTURE_UID)));
+		let request = fetch_request(&state.handle(Event::SubmitUid));
+		assert_eq!(state.uid(), Some(FIXTURE_UID));
+
+		let effects = state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		assert_eq!(effects, vec![Effect::SaveUid(FIXTURE_UID.to_string())]);
+	}
+
+	#[test]
+	fn failed_first_load_returns_to_dialog() {
+		let (mut state, _) = AppState::start(None, Settings::default());
+		state.handle(Event::UidInput(FIXTURE_UID.to_string()));
+		let request = fetch_request(&state.handle(Event::SubmitUid));
+
+		let effects = state.handle(Event::Fetched { request, result: Err("offline".to_string()) });
+		assert!(effects.is_empty());
+		match &state.phase {
+			Phase::NoUid { input, error } => {
+				assert_eq!(input, FIXTURE_UID);
+				assert!(error.as_deref().unwrap().contains("offline"));
+			}
+			_ => panic!("expected the UID dialog"),
+		}
+	}
+
+	#[test]
+	fn failed_load_of_saved_uid_is_an_error() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		state.handle(Event::Fetched { request, result: Err("offline".to_string()) });
+		assert!(matches!(state.phase, Phase::Error { .. }));
+
+		fetch_request(&state.handle(Event::Refresh));
+		assert!(matches!(state.phase, Phase::Loading { confirmed: true, .. }));
+	}
+
+	#[test]
+	fn logout_during_load_drops_the_result() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+
+		assert_eq!(state.handle(Event::Logout), vec![Effect::ForgetUid]);
+		let effects = state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		assert!(effects.is_empty());
+		assert!(matches!(state.phase, Phase::NoUid { .. }));
+		assert!(state.roster().is_none());
+	}
+
+	#[test]
+	fn stale_result_does_not_replace_newer_uid() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let stale = fetch_request(&effects);
+		state.handle(Event::Logout);
+		state.handle(Event::UidInput("800000001".to_string()));
+		let request = fetch_request(&state.handle(Event::SubmitUid));
+
+		state.handle(Event::Fetched { request: stale, result: Ok(fixture_roster()) });
+		assert!(matches!(state.phase, Phase::Loading { .. }));
+		state.handle(Event::Fetched { request, result: Err("offline".to_string()) });
+		assert!(matches!(state.phase, Phase::NoUid { .. }));
+	}
+
+	#[test]
+	fn refresh_keeps_roster_and_selection() {
+		let mut state = loaded();
+		state.handle(Event::Select(Some(3)));
+		let request = fetch_request(&state.handle(Event::Refresh));
+		assert!(matches!(state.phase, Phase::Refreshing { .. }));
+		assert!(state.selected().is_some());
+
+		// A second click while refreshing does not start another fetch
+		assert!(state.handle(Event::Refresh).is_empty());
+
+		state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		assert!(matches!(state.phase, Phase::Loaded { .. }));
+		assert_eq!(state.selected_character, Some(3));
+	}
+
+	fn builds_only() -> Roster {
+		Roster {
+			calculations: Value::Null,
+			..fixture_roster()
+		}
+	}
+
+	fn calculations() -> Value {
+		fixture_roster().calculations
+	}
+
+	#[test]
+	fn builds_show_before_calculations() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		state.handle(Event::Fetched { request, result: Ok(builds_only()) });
+		assert!(matches!(state.phase, Phase::Loaded { .. }));
+		assert_eq!(state.calculations, CalculationStatus::Loading(request));
+		assert!(state.view().unwrap().characters.iter().all(|char| char.calculation.is_none()));
+
+		let effects = state.handle(Event::CalculationsFetched { request, result: Ok(calculations()) });
+		assert_eq!(effects, vec![Effect::StoreResponse { uid: FIXTURE_UID.to_string() }]);
+		assert_eq!(state.calculations, CalculationStatus::Done);
+		assert!(state.view().unwrap().characters.iter().any(|char| char.calculation.is_some()));
+	}
+
+	#[test]
+	fn failed_calculations_can_be_retried() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		state.handle(Event::Fetched { request, result: Ok(builds_only()) });
+		let effects = state.handle(Event::CalculationsFetched { request, result: Err("503".to_string()) });
+		assert_eq!(effects, vec![Effect::StoreResponse { uid: FIXTURE_UID.to_string() }]);
+		assert_eq!(state.calculations, CalculationStatus::Failed("503".to_string()));
+		assert_eq!(state.roster().unwrap().characters.len(), 15);
+		assert!(state.roster().unwrap().calculations.is_null());
+
+		let retry = match state.handle(Event::RetryCalculations).as_slice() {
+			[Effect::FetchCalculations { uid, request }] if uid == FIXTURE_UID => *request,
+			effects => panic!("expected a calculations fetch, got {:?}", effects),
+		};
+		// Only one retry at a time, and the old request no longer counts
+		assert!(state.handle(Event::RetryCalculations).is_empty());
+		assert!(state.handle(Event::CalculationsFetched { request, result: Ok(calculations()) }).is_empty());
+		assert_eq!(state.calculations, CalculationStatus::Loading(retry));
+
+		state.handle(Event::CalculationsFetched { request: retry, result: Ok(calculations()) });
+		assert_eq!(state.calculations, CalculationStatus::Done);
+		assert!(state.roster().unwrap().calculations.is_array());
+	}
+
+	#[test]
+	fn failed_calculations_can_be_retried_while_refreshing() {
+		let mut state = loaded();
+		let request = fetch_request(&state.handle(Event::Refresh));
+		state.handle(Event::Fetched { request, result: Ok(builds_only()) });
+		state.handle(Event::CalculationsFetched { request, result: Err("503".to_string()) });
+		// The old calculations went with the failed ones
+		assert!(state.view().unwrap().characters.iter().all(|char| char.calculation.is_none()));
+
+		let refresh = fetch_request(&state.handle(Event::Refresh));
+		assert!(matches!(state.phase, Phase::Refreshing { .. }));
+		let retry = match state.handle(Event::RetryCalculations).as_slice() {
+			[Effect::FetchCalculations { uid, request }] if uid == FIXTURE_UID => *request,
+			effects => panic!("expected a calculations fetch, got {:?}", effects),
+		};
+		assert_ne!(retry, refresh);
+		let effects = state.handle(Event::CalculationsFetched { request: retry, result: Ok(calculations()) });
+		assert_eq!(effects, vec![Effect::StoreResponse { uid: FIXTURE_UID.to_string() }]);
+		assert!(matches!(state.phase, Phase::Refreshing { .. }));
+		assert!(state.view().unwrap().characters.iter().any(|char| char.calculation.is_some()));
+	}
+
+	#[test]
+	fn refresh_keeps_calculations_until_new_ones_arrive() {
+		let mut state = loaded();
+		let request = fetch_request(&state.handle(Event::Refresh));
+		state.handle(Event::Fetched { request, result: Ok(builds_only()) });
+		assert_eq!(state.roster().unwrap().calculations, calculations());
+		assert_eq!(state.calculations, CalculationStatus::Loading(request));
+
+		// Logging out drops whatever is still in flight
+		state.handle(Event::Logout);
+		assert!(state.handle(Event::CalculationsFetched { request, result: Ok(calculations()) }).is_empty());
+		assert_eq!(state.calculations, CalculationStatus::Done);
+	}
+
+	#[test]
+	fn failed_refresh_is_an_error() {
+		let mut state = loaded();
+		state.handle(Event::Select(Some(0)));
+		let request = fetch_request(&state.handle(Event::Refresh));
+		state.handle(Event::Fetched { request, result: Err("offline".to_string()) });
+
+		assert!(matches!(&state.phase, Phase::Error { message, .. } if message == "offline"));
+		assert_eq!(state.selected_character, None);
+	}
+
+	#[test]
+	fn cursor_follows_the_visible_list() {
+		let mut state = loaded();
+		let visible = state.visible_characters();
+		assert_eq!(visible, state.roster().unwrap().sorted_indices(SortOrder::CritValue));
+
+		state.handle(Event::MoveCursor(1));
+		assert_eq!(state.list_cursor, Some(visible[0]));
+		state.handle(Event::MoveCursor(1));
+		state.handle(Event::MoveCursor(-5));
+		assert_eq!(state.list_cursor, Some(visible[0]));
+		state.handle(Event::MoveCursor(100));
+		assert_eq!(state.list_cursor, visible.last().copied());
+
+		state.handle(Event::OpenCursor);
+		assert_eq!(state.selected_character, visible.last().copied());
+	}
+
+	#[test]
+	fn search_filters_the_list() {
+		let mut state = loaded();
+		let other = state.roster().unwrap().characters.iter().position(|c| c["name"] != "Furina");
+		state.handle(Event::Select(other));
+		state.handle(Event::Search("fUrI".to_string()));
+		let visible = state.visible_characters();
+		assert_eq!(visible.len(), 1);
+		assert_eq!(state.roster().unwrap().characters[visible[0]]["name"], "Furina");
+		// The cursor was on someone else, who is now hidden
+		assert_eq!(state.list_cursor, None);
+
+		state.handle(Event::MoveCursor(-1));
+		assert_eq!(state.list_cursor, Some(visible[0]));
+		state.handle(Event::Search("nobody".to_string()));
+		assert_eq!(state.list_cursor, None);
+		state.handle(Event::MoveCursor(1));
+		assert_eq!(state.list_cursor, None);
+	}
+
+	#[test]
+	fn escape_closes_the_topmost_overlay() {
+		let mut state = loaded();
+		state.handle(Event::ShowSettings(true));
+		state.handle(Event::ShowHelp(true));
+		state.handle(Event::Notice("Exported".to_string()));
+
+		state.handle(Event::CloseOverlay);
+		assert!(!state.help_open && state.settings_open);
+		state.handle(Event::CloseOverlay);
+		assert!(!state.settings_open && state.notice.is_some());
+		state.handle(Event::CloseOverlay);
+		assert!(state.notice.is_none());
+	}
+
+	#[test]
+	fn opened_files_replace_the_account() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		let files = vec![PathBuf::from("builds.json")];
+		state.handle(Event::Opened { files: files.clone(), result: Ok(fixture_roster()) });
+		assert!(matches!(&state.phase, Phase::Opened { files: opened, .. } if *opened == files));
+		assert_eq!(state.uid(), None);
+		assert!(state.roster().is_some());
+		assert_eq!(state.view().map(|view| view.characters.len()), Some(15));
+
+		// Neither the fetch that was running nor a refresh replaces the file
+		assert!(state.handle(Event::Fetched { request, result: Ok(fixture_roster()) }).is_empty());
+		assert!(state.handle(Event::Refresh).is_empty());
+		assert!(matches!(state.phase, Phase::Opened { .. }));
+
+		state.handle(Event::Select(Some(1)));
+		assert!(state.selected().is_some());
+		// Closing loads the saved UID again, its first fetch was ignored
+		let effects = state.handle(Event::CloseFile);
+		assert_ne!(fetch_request(&effects), request);
+		assert!(matches!(state.phase, Phase::Loading { confirmed: true, .. }));
+		assert_eq!(state.uid(), Some(FIXTURE_UID));
+		assert!(state.selected().is_none());
+		assert!(state.view().is_none());
+	}
+
+	#[test]
+	fn closing_a_file_shows_the_account_again() {
+		let mut state = loaded();
+		state.handle(Event::Opened { files: vec![PathBuf::from("a.json")], result: Ok(fixture_roster()) });
+		state.handle(Event::Opened { files: vec![PathBuf::from("b.json")], result: Ok(fixture_roster()) });
+		assert_eq!(state.uid(), None);
+
+		assert!(state.handle(Event::CloseFile).is_empty());
+		assert!(matches!(&state.phase, Phase::Loaded { uid, .. } if uid == FIXTURE_UID));
+		assert_eq!(state.view().map(|view| view.characters.len()), Some(15));
+
+		// Nothing to go back to after logging out
+		state.handle(Event::Opened { files: vec![PathBuf::from("a.json")], result: Ok(fixture_roster()) });
+		state.handle(Event::Logout);
+		state.handle(Event::Opened { files: vec![PathBuf::from("a.json")], result: Ok(fixture_roster()) });
+		assert!(state.handle(Event::CloseFile).is_empty());
+		assert!(matches!(state.phase, Phase::NoUid { .. }));
+	}
+
+	#[test]
+	fn open_dialog_reports_errors() {
+		let mut state = loaded();
+		state.handle(Event::ShowOpen(true));
+		assert!(state.handle(Event::SubmitOpen).is_empty());
+		assert!(state.open_dialog.as_ref().unwrap().error.is_some());
+
+		state.handle(Event::OpenInput("\"/tmp/builds.json\"\n\n /tmp/calculations.json ".to_string()));
+		let files = vec![PathBuf::from("/tmp/builds.json"), PathBuf::from("/tmp/calculations.json")];
+		assert_eq!(state.handle(Event::SubmitOpen), vec![Effect::OpenFiles(files.clone())]);
+
+		// A failed open keeps showing the account
+		state.handle(Event::ShowOpen(false));
+		state.handle(Event::Opened { files, result: Err("Not akasha JSON".to_string()) });
+		let dialog = state.open_dialog.clone().unwrap();
+		assert_eq!(dialog.input, "/tmp/builds.json\n/tmp/calculations.json");
+		assert_eq!(dialog.error.as_deref(), Some("Not akasha JSON"));
+		assert_eq!(state.uid(), Some(FIXTURE_UID));
+
+		state.handle(Event::CloseOverlay);
+		assert!(state.open_dialog.is_none());
+	}
+
+	#[test]
+	fn export_needs_a_roster() {
+		let (mut state, _) = AppState::start(None, Settings::default());
+		assert!(state.handle(Event::Export).is_empty());
+
+		let mut state = loaded();
+		state.handle(Event::Select(Some(2)));
+		assert_eq!(
+			state.handle(Event::Export),
+			vec![Effect::Export {
+				uid: FIXTURE_UID.to_string(),
+				selected: Some(2)
+			}]
+		);
+	}
+
+	#[test]
+	fn tab_and_history_survive_switching_characters() {
+		let mut state = loaded();
+		state.handle(Event::Select(Some(0)));
+		assert_eq!(
+			state.handle(Event::ShowTab(DetailTab::History)),
+			vec![Effect::LoadHistory { uid: FIXTURE_UID.to_string() }]
+		);
+		state.handle(Event::HistoryLoaded {
+			uid: "800000001".to_string(),
+			snapshots: Vec::new(),
+		});
+		assert!(state.history.is_none());
+		state.handle(Event::HistoryLoaded {
+			uid: FIXTURE_UID.to_string(),
+			snapshots: Vec::new(),
+		});
+		assert!(state.history.is_some());
+
+		state.handle(Event::Select(Some(1)));
+		assert_eq!(state.detail_tab, DetailTab::History);
+		assert!(state.handle(Event::ShowTab(DetailTab::History)).is_empty());
+
+		// A refresh may add a snapshot, the open tab reloads
+		let request = fetch_request(&state.handle(Event::Refresh));
+		let effects = state.handle(Event::Fetched { request, result: Ok(fixture_roster()) });
+		assert_eq!(effects, vec![Effect::LoadHistory { uid: FIXTURE_UID.to_string() }]);
+	}
+
+	#[test]
+	fn settings_are_saved_when_they_change() {
+		let mut state = loaded();
+		let mut settings = state.settings.clone();
+		assert!(state.handle(Event::UpdateSettings(settings.clone())).is_empty());
+
+		settings.ui_scale = 1.25;
+		assert_eq!(state.handle(Event::UpdateSettings(settings)), vec![Effect::SaveSettings]);
+		assert_eq!(state.settings.ui_scale, 1.25);
+		// Changing settings never interrupts what is on screen
+		assert!(matches!(state.phase, Phase::Loaded { .. }));
+
+		state.handle(Event::Logout);
+		assert_eq!(state.settings.ui_scale, 1.25);
+	}
+
+	#[test]
+	fn calculation_only_characters_can_be_selected() {
+		let (mut state, effects) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		let request = fetch_request(&effects);
+		let mut roster = builds_only();
+		roster.characters.retain(|char| char["name"] != "Xiangling");
+		state.handle(Event::Fetched { request, result: Ok(roster) });
+		assert_eq!(state.visible_characters().len(), 14);
+
+		state.handle(Event::CalculationsFetched { request, result: Ok(calculations()) });
+		assert_eq!(state.visible_characters().len(), 15);
+		state.handle(Event::Select(Some(14)));
+		assert_eq!(state.selected().unwrap()["name"], "Xiangling");
+		assert!(state.selected_calculation().is_some());
+	}
+
+	#[test]
+	fn saved_builds_keep_the_cursor_on_their_character() {
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(variant_roster("Furina", "Saved"));
+		assert_eq!(state.visible_characters().len(), 15);
+
+		state.handle(Event::Select(Some(15)));
+		assert_eq!(state.selected().unwrap()["type"], "Saved");
+		assert_eq!(state.list_cursor, Some(0));
+		let fit = |state: &AppState| state.selected_calculation().unwrap()["calculations"]["fit"]["ranking"].clone();
+		let saved_ranking = fit(&state);
+		state.handle(Event::Select(Some(0)));
+		assert_ne!(fit(&state), saved_ranking);
+	}
+
+	#[test]
+	fn selection_is_bounded_by_roster() {
+		let mut state = loaded();
+		state.handle(Event::Select(Some(99)));
+		assert_eq!(state.selected_character, None);
+		state.handle(Event::Select(Some(1)));
+		assert_eq!(state.selected().unwrap()["name"], state.roster().unwrap().characters[1]["name"]);
+	}
+}
@@ -0,0 +1,415 @@
+use serde_json::Value;
+
+use crate::utils;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatKind {
+	BaseHp,
+	Hp,
+	HpPercent,
+	BaseAtk,
+	Atk,
+	AtkPercent,
+	BaseDef,
+	Def,
+	DefPercent,
+	CritRate,
+	CritDamage,
+	EnergyRecharge,
+	ElementalMastery,
+	HealingBonus,
+	IncomingHealingBonus,
+	PhysicalDamageBonus,
+	PyroDamageBonus,
+	HydroDamageBonus,
+	DendroDamageBonus,
+	ElectroDamageBonus,
+	AnemoDamageBonus,
+	CryoDamageBonus,
+	GeoDamageBonus,
+}
+
+// Which of the bundled stat icons represents a stat
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatIcon {
+	Hp,
+	Atk,
+	Def,
+	CritRate,
+	CritDmg,
+	Er,
+	Em,
+}
+
+// Flat stats are shown as whole numbers, percentages with one decimal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatFormat {
+	Flat,
+	Percent,
+}
+
+pub struct StatInfo {
+	pub kind: StatKind,
+	pub name: &'static str,
+	pub format: StatFormat,
+	// FIGHT_PROP_* identifiers used by enka (weapon stats, substats)
+	pub fight_props: &'static [&'static str],
+	// Display keys used by akasha (artifact main stats, weapon substats)
+	pub keys: &'static [&'static str],
+	// Key inside a build's `stats` object
+	pub stats_key: Option<&'static str>,
+	pub icon: Option<StatIcon>,
+}
+
+pub static STATS: &[StatInfo] = &[
+	StatInfo {
+		kind: StatKind::BaseHp,
+		name: "Base HP",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_BASE_HP"],
+		keys: &["Base HP"],
+		stats_key: None,
+		icon: Some(StatIcon::Hp),
+	},
+	StatInfo {
+		kind: StatKind::Hp,
+		name: "HP",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_HP", "FIGHT_PROP_MAX_HP"],
+		keys: &["HP", "Flat HP"],
+		stats_key: Some("maxHp"),
+		icon: Some(StatIcon::Hp),
+	},
+	StatInfo {
+		kind: StatKind::HpPercent,
+		name: "HP%",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_HP_PERCENT"],
+		keys: &["HP%"],
+		stats_key: None,
+		icon: Some(StatIcon::Hp),
+	},
+	StatInfo {
+		kind: StatKind::BaseAtk,
+		name: "Base ATK",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_BASE_ATTACK"],
+		keys: &["Base ATK"],
+		stats_key: None,
+		icon: Some(StatIcon::Atk),
+	},
+	StatInfo {
+		kind: StatKind::Atk,
+		name: "ATK",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_ATTACK", "FIGHT_PROP_CUR_ATTACK"],
+		keys: &["ATK", "Flat ATK"],
+		stats_key: Some("atk"),
+		icon: Some(StatIcon::Atk),
+	},
+	StatInfo {
+		kind: StatKind::AtkPercent,
+		name: "ATK%",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_ATTACK_PERCENT"],
+		keys: &["ATK%"],
+		stats_key: None,
+		icon: Some(StatIcon::Atk),
+	},
+	StatInfo {
+		kind: StatKind::BaseDef,
+		name: "Base DEF",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_BASE_DEFENSE"],
+		keys: &["Base DEF"],
+		stats_key: None,
+		icon: Some(StatIcon::Def),
+	},
+	StatInfo {
+		kind: StatKind::Def,
+		name: "DEF",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_DEFENSE", "FIGHT_PROP_CUR_DEFENSE"],
+		keys: &["DEF", "Flat DEF"],
+		stats_key: Some("def"),
+		icon: Some(StatIcon::Def),
+	},
+	StatInfo {
+		kind: StatKind::DefPercent,
+		name: "DEF%",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_DEFENSE_PERCENT"],
+		keys: &["DEF%"],
+		stats_key: None,
+		icon: Some(StatIcon::Def),
+	},
+	StatInfo {
+		kind: StatKind::CritRate,
+		name: "Crit Rate",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_CRITICAL"],
+		keys: &["Crit RATE"],
+		stats_key: Some("critRate"),
+		icon: Some(StatIcon::CritRate),
+	},
+	StatInfo {
+		kind: StatKind::CritDamage,
+		name: "Crit DMG",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_CRITICAL_HURT"],
+		keys: &["Crit DMG"],
+		stats_key: Some("critDamage"),
+		icon: Some(StatIcon::CritDmg),
+	},
+	StatInfo {
+		kind: StatKind::EnergyRecharge,
+		name: "Energy Recharge",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_CHARGE_EFFICIENCY"],
+		keys: &["Energy Recharge", "ER"],
+		stats_key: Some("energyRecharge"),
+		icon: Some(StatIcon::Er),
+	},
+	StatInfo {
+		kind: StatKind::ElementalMastery,
+		name: "Elemental Mastery",
+		format: StatFormat::Flat,
+		fight_props: &["FIGHT_PROP_ELEMENT_MASTERY"],
+		keys: &["Elemental Mastery", "EM"],
+		stats_key: Some("elementalMastery"),
+		icon: Some(StatIcon::Em),
+	},
+	StatInfo {
+		kind: StatKind::HealingBonus,
+		name: "Healing Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_HEAL_ADD"],
+		keys: &["Healing Bonus"],
+		stats_key: Some("healingBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::IncomingHealingBonus,
+		name: "Incoming Healing Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_HEALED_ADD"],
+		keys: &["Incoming Healing Bonus"],
+		stats_key: Some("incomingHealingBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::PhysicalDamageBonus,
+		name: "Physical DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_PHYSICAL_ADD_HURT"],
+		keys: &["Physical DMG Bonus"],
+		stats_key: Some("physicalDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::PyroDamageBonus,
+		name: "Pyro DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_FIRE_ADD_HURT"],
+		keys: &["Pyro DMG Bonus"],
+		stats_key: Some("pyroDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::HydroDamageBonus,
+		name: "Hydro DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_WATER_ADD_HURT"],
+		keys: &["Hydro DMG Bonus"],
+		stats_key: Some("hydroDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::DendroDamageBonus,
+		name: "Dendro DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_GRASS_ADD_HURT"],
+		keys: &["Dendro DMG Bonus"],
+		stats_key: Some("dendroDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::ElectroDamageBonus,
+		name: "Electro DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_ELEC_ADD_HURT"],
+		keys: &["Electro DMG Bonus"],
+		stats_key: Some("electroDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::AnemoDamageBonus,
+		name: "Anemo DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_WIND_ADD_HURT"],
+		keys: &["Anemo DMG Bonus"],
+		stats_key: Some("anemoDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::CryoDamageBonus,
+		name: "Cryo DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_ICE_ADD_HURT"],
+		keys: &["Cryo DMG Bonus"],
+		stats_key: Some("cryoDamageBonus"),
+		icon: None,
+	},
+	StatInfo {
+		kind: StatKind::GeoDamageBonus,
+		name: "Geo DMG Bonus",
+		format: StatFormat::Percent,
+		fight_props: &["FIGHT_PROP_ROCK_ADD_HURT"],
+		keys: &["Geo DMG Bonus"],
+		stats_key: Some("geoDamageBonus"),
+		icon: None,
+	},
+];
+
+// Stats that are always listed for a character, in display order
+pub const CORE_STATS: &[StatKind] = &[
+	StatKind::Hp,
+	StatKind::Atk,
+	StatKind::Def,
+	StatKind::CritRate,
+	StatKind::CritDamage,
+	StatKind::EnergyRecharge,
+	StatKind::ElementalMastery,
+];
+
+// Stats that are only listed for a character when non-zero
+pub const BONUS_STATS: &[StatKind] = &[
+	StatKind::HealingBonus,
+	StatKind::PhysicalDamageBonus,
+	StatKind::PyroDamageBonus,
+	StatKind::HydroDamageBonus,
+	StatKind::DendroDamageBonus,
+	StatKind::ElectroDamageBonus,
+	StatKind::AnemoDamageBonus,
+	StatKind::CryoDamageBonus,
+	StatKind::GeoDamageBonus,
+];
+
+impl StatKind {
+	pub fn info(self) -> &'static StatInfo {
+		STATS
+			.iter()
+			.find(|info| info.kind == self)
+			.expect("every StatKind has a registry entry")
+	}
+
+	pub fn name(self) -> &'static str {
+		self.info().name
+	}
+
+	pub fn icon(self) -> Option<StatIcon> {
+		self.info().icon
+	}
+
+	pub fn is_percent(self) -> bool {
+		self.info().format == StatFormat::Percent
+	}
+
+	pub fn from_fight_prop(prop: &str) -> Option<Self> {
+		STATS
+			.iter()
+			.find(|info| info.fight_props.contains(&prop))
+			.map(|info| info.kind)
+	}
+
+	// Akasha is inconsistent with casing ("Crit RATE", "CRIT Rate"), so match loosely
+	pub fn from_key(key: &str) -> Option<Self> {
+		let key = key.trim();
+		STATS
+			.iter()
+			.find(|info| {
+				info.name.eq_ignore_ascii_case(key)
+					|| info.keys.iter().any(|k| k.eq_ignore_ascii_case(key))
+			})
+			.map(|info| info.kind)
+	}
+
+	// Percent values are expected as fractions (0.5 == 50%), like in a build's `stats`
+	pub fn format(self, value: f64) -> String {
+		self.format_in(value, utils::NumberLocale::default())
+	}
+
+	pub fn format_in(self, value: f64, locale: utils::NumberLocale) -> String {
+		match self.info().format {
+			StatFormat::Percent => format!("{}%", utils::format_decimal(value * 100.0, 1, locale)),
+			StatFormat::Flat => utils::format_decimal(value, 0, locale),
+		}
+	}
+}
+
+// Reads a stat from a build's `stats` object
+pub fn character_stat(char: &Value, kind: StatKind) -> Option<f64> {
+	let key = kind.info().stats_key?;
+	char["stats"][key]["value"].as_f64()
+}
+
+// Decodes an enka `{ stat: "FIGHT_PROP_*", statValue }` entry. Enka sends percent
+// stats as percentage points, so they are normalised to fractions here.
+pub fn weapon_stat(entry: &Value) -> Option<(StatKind, f64)> {
+	let kind = StatKind::from_fight_prop(entry["stat"].as_str()?)?;
+	let value = entry["statValue"].as_f64()?;
+	if kind.is_percent() {
+		Some((kind, value / 100.0))
+	} else {
+		Some((kind, value))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::FIXTURE_CALCULATIONS;
+
+	// Xiangling's weapon in the calculations fixture, a flat and a percent stat
+	fn fixture_weapon_stats() -> Vec<Value> {
+		let calcs: Value = serde_json::from_str(FIXTURE_CALCULATIONS).unwrap();
+		let xiangling = calcs["data"].as_array().unwrap().iter().find(|calc| calc["name"] == "Xiangling").unwrap();
+		xiangling["weapon"]["flat"]["weaponStats"].as_array().unwrap().clone()
+	}
+
+	#[test]
+	fn decodes_enka_weapon_stats() {
+		let entries = fixture_weapon_stats();
+		assert_eq!(weapon_stat(&entries[0]), Some((StatKind::BaseAtk, 449.0)));
+		// Percentage points become fractions
+		let (kind, value) = weapon_stat(&entries[1]).unwrap();
+		assert_eq!(kind, StatKind::EnergyRecharge);
+		assert!((value - 0.419).abs() < 1e-9);
+
+		assert_eq!(weapon_stat(&serde_json::json!({ "stat": "FIGHT_PROP_UNKNOWN", "statValue": 1 })), None);
+		assert_eq!(weapon_stat(&serde_json::json!({ "stat": "FIGHT_PROP_BASE_ATTACK" })), None);
+	}
+
+	#[test]
+	fn looks_up_fight_props_and_keys() {
+		assert_eq!(StatKind::from_fight_prop("FIGHT_PROP_CHARGE_EFFICIENCY"), Some(StatKind::EnergyRecharge));
+		assert_eq!(StatKind::from_fight_prop("FIGHT_PROP_BASE_ATTACK"), Some(StatKind::BaseAtk));
+		assert_eq!(StatKind::from_fight_prop("Energy Recharge"), None);
+
+		assert_eq!(StatKind::from_key("Crit RATE"), Some(StatKind::CritRate));
+		assert_eq!(StatKind::from_key(" crit dmg "), Some(StatKind::CritDamage));
+		assert_eq!(StatKind::from_key("HP%"), Some(StatKind::HpPercent));
+		assert_eq!(StatKind::from_key("Charisma"), None);
+	}
+
+	#[test]
+	fn formats_percent_and_flat_stats() {
+		let (kind, value) = weapon_stat(&fixture_weapon_stats()[1]).unwrap();
+		assert_eq!(kind.format(value), "41.9%");
+		assert_eq!(kind.format_in(value, utils::NumberLocale::German), "41,9%");
+		assert_eq!(StatKind::BaseAtk.format(449.0), "449");
+		assert_eq!(StatKind::Hp.format_in(38550.63, utils::NumberLocale::German), "38.551");
+		assert!(StatKind::CritRate.is_percent());
+		assert!(!StatKind::ElementalMastery.is_percent());
+	}
+}
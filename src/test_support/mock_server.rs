@@ -0,0 +1,161 @@
+// Local stand-in for akasha.cv and enka.network so tests can run offline.
+// Serves the example JSON dumps and the bundled stat icons on an ephemeral port.
+// Only uses std, integration tests include it with `#[path]`.
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const FIXTURE_UID: &str = "772493838";
+pub const FIXTURE_BUILDS: &str = include_str!("../../example_build.json");
+pub const FIXTURE_CALCULATIONS: &str = include_str!("../../example_calculation.json");
+
+#[derive(Clone)]
+pub enum Reply {
+	Json(String),
+	Bytes(&'static str, Vec<u8>),
+	Status(u16),
+	Slow(Duration, Box<Reply>),
+}
+
+pub struct MockServer {
+	addr: SocketAddr,
+	// Most recent override whose prefix matches the request path wins
+	overrides: Arc<Mutex<Vec<(String, Reply)>>>,
+	requests: Arc<Mutex<Vec<String>>>,
+	shutdown: Arc<AtomicBool>,
+}
+
+impl MockServer {
+	pub fn start() -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+		let server = Self {
+			addr: listener.local_addr().unwrap(),
+			overrides: Arc::new(Mutex::new(Vec::new())),
+			requests: Arc::new(Mutex::new(Vec::new())),
+			shutdown: Arc::new(AtomicBool::new(false)),
+		};
+
+		let overrides = server.overrides.clone();
+		let requests = server.requests.clone();
+		let shutdown = server.shutdown.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				if shutdown.load(Ordering::SeqCst) {
+					break;
+				}
+				let Ok(stream) = stream else {
+					continue;
+				};
+				let overrides = overrides.clone();
+				let requests = requests.clone();
+				thread::spawn(move || handle(stream, &overrides, &requests));
+			}
+		});
+
+		server
+	}
+
+	pub fn url(&self) -> String {
+		format!("http://{}", self.addr)
+	}
+
+	pub fn api_url(&self) -> String {
+		format!("{}/api", self.url())
+	}
+
+	#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+	pub fn image_url(&self, name: &str) -> String {
+		format!("{}/ui/{}.png", self.url(), name)
+	}
+
+	pub fn reply(&self, path_prefix: &str, reply: Reply) {
+		self.overrides.lock().unwrap().push((path_prefix.to_string(), reply));
+	}
+
+	pub fn hits(&self, path_prefix: &str) -> usize {
+		self.requests
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|path| path.starts_with(path_prefix))
+			.count()
+	}
+}
+
+impl Drop for MockServer {
+	fn drop(&mut self) {
+		self.shutdown.store(true, Ordering::SeqCst);
+		// Wake up the accept loop so it sees the flag
+		TcpStream::connect(self.addr).ok();
+	}
+}
+
+fn handle(mut stream: TcpStream, overrides: &Mutex<Vec<(String, Reply)>>, requests: &Mutex<Vec<String>>) {
+	let mut reader = BufReader::new(stream.try_clone().unwrap());
+	let mut request_line = String::new();
+	if reader.read_line(&mut request_line).is_err() {
+		return;
+	}
+	// Drain headers, nothing we serve depends on them
+	let mut line = String::new();
+	while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+		line.clear();
+	}
+
+	let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+	requests.lock().unwrap().push(path.clone());
+
+	let reply = overrides
+		.lock()
+		.unwrap()
+		.iter()
+		.rev()
+		.find(|(prefix, _)| path.starts_with(prefix.as_str()))
+		.map(|(_, reply)| reply.clone())
+		.unwrap_or_else(|| default_reply(&path));
+	write_reply(&mut stream, reply);
+}
+
+fn default_reply(path: &str) -> Reply {
+	if path.starts_with("/api/builds/") {
+		Reply::Json(FIXTURE_BUILDS.to_string())
+	} else if path.starts_with("/api/getCalculationsForUser/") {
+		Reply::Json(FIXTURE_CALCULATIONS.to_string())
+	} else if path.starts_with("/api/user/refresh/") {
+		Reply::Json("{\"data\":{}}".to_string())
+	} else if let Some(name) = path.strip_prefix("/ui/") {
+		let name = name.split('?').next().unwrap_or(name);
+		let file = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/icons").join(name);
+		match std::fs::read(file) {
+			Ok(bytes) => Reply::Bytes("image/png", bytes),
+			Err(_) => Reply::Status(404),
+		}
+	} else {
+		Reply::Status(404)
+	}
+}
+
+fn write_reply(stream: &mut TcpStream, reply: Reply) {
+	let (status, content_type, body) = match reply {
+		Reply::Json(body) => (200, "application/json", body.into_bytes()),
+		Reply::Bytes(content_type, body) => (200, content_type, body),
+		Reply::Status(status) => (status, "text/plain", Vec::new()),
+		Reply::Slow(delay, reply) => {
+			thread::sleep(delay);
+			return write_reply(stream, *reply);
+		}
+	};
+	let head = format!(
+		"HTTP/1.1 {} MOCK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+		status,
+		content_type,
+		body.len()
+	);
+	stream.write_all(head.as_bytes()).ok();
+	stream.write_all(&body).ok();
+	stream.flush().ok();
+}
@@ -0,0 +1,154 @@
+#[cfg(feature = "gui")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "gui")]
+use eframe::egui;
+
+use crate::models::Roster;
+use crate::utils::ApiClient;
+
+mod mock_server;
+pub use mock_server::*;
+
+pub fn fixture_roster() -> Roster {
+	let builds: serde_json::Value = serde_json::from_str(FIXTURE_BUILDS).unwrap();
+	let calcs: serde_json::Value = serde_json::from_str(FIXTURE_CALCULATIONS).unwrap();
+	Roster {
+		characters: builds["data"].as_array().cloned().unwrap(),
+		calculations: calcs["data"].clone(),
+	}
+}
+
+// The fixture plus a saved `build_type` build of `name` with a lower crit value and
+// a worse leaderboard entry than their current one
+pub fn variant_roster(name: &str, build_type: &str) -> Roster {
+	let mut roster = fixture_roster();
+	let mut build = roster.characters.iter().find(|c| c["name"] == name).cloned().unwrap();
+	let id = build["characterId"].as_i64().unwrap();
+	let calc = roster.find_calculation(id, "current").cloned();
+
+	build["_id"] = format!("{}-{}", build["_id"].as_str().unwrap(), build_type).into();
+	build["type"] = build_type.into();
+	build["critValue"] = 150.0.into();
+	roster.characters.push(build);
+	if let Some(mut calc) = calc {
+		calc["type"] = build_type.into();
+		let fit = &mut calc["calculations"]["fit"];
+		fit["ranking"] = (fit["ranking"].as_i64().unwrap() + 100).into();
+		roster.calculations.as_array_mut().unwrap().push(calc);
+	}
+	roster
+}
+
+// A fresh, empty directory under the system temp dir
+pub fn temp_dir(name: &str) -> PathBuf {
+	static NEXT: AtomicUsize = AtomicUsize::new(0);
+	let dir = std::env::temp_dir().join(format!(
+		"genshin-viewer-test-{}-{}-{}",
+		std::process::id(),
+		name,
+		NEXT.fetch_add(1, Ordering::Relaxed)
+	));
+	std::fs::remove_dir_all(&dir).ok();
+	std::fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+impl MockServer {
+	pub fn client(&self) -> ApiClient {
+		ApiClient::with_timeout(&self.api_url(), Duration::from_secs(2))
+	}
+}
+
+// Runs `run_ui` for a few frames on a headless context of the given size and returns
+// the output of the last frame. Areas only know their size after the first frame.
+#[cfg(feature = "gui")]
+pub fn render_headless(size: egui::Vec2, mut run_ui: impl FnMut(&egui::Context)) -> egui::FullOutput {
+	let ctx = egui::Context::default();
+	let input = || egui::RawInput {
+		screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+		pixels_per_point: Some(1.0),
+		..Default::default()
+	};
+	let _ = ctx.run(input(), &mut run_ui);
+	ctx.run(input(), &mut run_ui)
+}
+
+// Every piece of text that was painted, with its on-screen rect, in paint order
+#[cfg(feature = "gui")]
+pub fn painted_text(output: &egui::FullOutput) -> Vec<(String, egui::Rect)> {
+	fn collect(shape: &egui::Shape, out: &mut Vec<(String, egui::Rect)>) {
+		match shape {
+			egui::Shape::Text(text) => {
+				out.push((text.galley.text().to_string(), text.galley.rect.translate(text.pos.to_vec2())));
+			}
+			egui::Shape::Vec(shapes) => {
+				for shape in shapes {
+					collect(shape, out);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	let mut out = Vec::new();
+	for egui::epaint::ClippedShape(_, shape) in &output.shapes {
+		collect(shape, &mut out);
+	}
+	out
+}
+
+// One line per painted text, positions rounded to whole points
+#[cfg(feature = "gui")]
+pub fn layout_snapshot(output: &egui::FullOutput) -> String {
+	painted_text(output)
+		.iter()
+		.map(|(text, rect)| {
+			format!(
+				"[{:>5} {:>5} {:>5} {:>5}] {}\n",
+				rect.min.x.round(),
+				rect.min.y.round(),
+				rect.max.x.round(),
+				rect.max.y.round(),
+				text.replace('\n', "\\n")
+			)
+		})
+		.collect()
+}
+
+// Compares against tests/snapshots/<name>.txt. Run with UPDATE_SNAPSHOTS=1 to accept changes.
+#[cfg(feature = "gui")]
+pub fn assert_snapshot(name: &str, actual: &str) {
+	let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+		.join("tests/snapshots")
+		.join(format!("{}.txt", name));
+
+	if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+		std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+		std::fs::write(&path, actual).unwrap();
+		return;
+	}
+
+	let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+		panic!("missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it", path.display())
+	});
+	if expected != actual {
+		let diff: Vec<String> = expected
+			.lines()
+			.zip(actual.lines())
+			.filter(|(e, a)| e != a)
+			.take(10)
+			.map(|(e, a)| format!("- {}\n+ {}", e, a))
+			.collect();
+		panic!(
+			"layout snapshot {} changed (expected {} lines, got {}):\n{}\nRun with UPDATE_SNAPSHOTS=1 to accept.",
+			name,
+			expected.lines().count(),
+			actual.lines().count(),
+			diff.join("\n")
+		);
+	}
+}
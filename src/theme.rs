@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+	#[default]
+	Dark,
+	Light,
+	HighContrast,
+}
+
+impl Theme {
+	pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Theme::Dark => "Dark",
+			Theme::Light => "Light",
+			Theme::HighContrast => "High contrast",
+		}
+	}
+
+	// Panel colour text is drawn on, matches egui's own visuals
+	pub fn background(self) -> Rgb {
+		match self {
+			Theme::Dark => (27, 27, 27),
+			Theme::Light => (248, 248, 248),
+			Theme::HighContrast => (0, 0, 0),
+		}
+	}
+
+	// WCAG AA for the normal themes, AAA for high contrast
+	pub fn min_contrast(self) -> f32 {
+		match self {
+			Theme::HighContrast => 7.0,
+			_ => 4.5,
+		}
+	}
+}
+
+pub const ELEMENTS: [&str; 7] = ["Pyro", "Hydro", "Anemo", "Electro", "Dendro", "Cryo", "Geo"];
+
+// The one element palette, the GUI and the CLI both use it
+pub fn element_rgb(element: &str) -> Rgb {
+	match element.to_lowercase().as_str() {
+		"hydro" => (0, 144, 255),
+		"pyro" => (255, 69, 0),
+		"cryo" => (167, 223, 236),
+		"electro" => (178, 132, 255),
+		"anemo" => (148, 255, 198),
+		"geo" => (255, 198, 93),
+		"dendro" => (147, 215, 65),
+		_ => (255, 255, 255),
+	}
+}
+
+fn luminance((r, g, b): Rgb) -> f32 {
+	let channel = |c: u8| {
+		let c = c as f32 / 255.0;
+		if c <= 0.03928 {
+			c / 12.92
+		} else {
+			((c + 0.055) / 1.055).powf(2.4)
+		}
+	};
+	0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+pub fn contrast(a: Rgb, b: Rgb) -> f32 {
+	let (a, b) = (luminance(a), luminance(b));
+	(a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+// `rgb` pushed towards black or white until it is readable on the theme's background
+pub fn readable(rgb: Rgb, theme: Theme) -> Rgb {
+	let background = theme.background();
+	let target: f32 = if luminance(background) > 0.5 { 0.0 } else { 255.0 };
+	let mix = |c: u8, t: f32| (c as f32 + (target - c as f32) * t).round() as u8;
+
+	let mut step = 0.0;
+	loop {
+		let color = (mix(rgb.0, step), mix(rgb.1, step), mix(rgb.2, step));
+		if step >= 1.0 || contrast(color, background) >= theme.min_contrast() {
+			return color;
+		}
+		step += 0.05;
+	}
+}
+
+pub fn element_text_rgb(element: &str, theme: Theme) -> Rgb {
+	readable(element_rgb(element), theme)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_element_is_readable_in_every_theme() {
+		for theme in Theme::ALL {
+			for element in ELEMENTS.iter().chain(&["unknown"]) {
+				let rgb = element_text_rgb(element, theme);
+				assert!(
+					contrast(rgb, theme.background()) >= theme.min_contrast(),
+					"{} on {:?} is {:?}",
+					element,
+					theme,
+					rgb
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn readable_colours_stay_untouched() {
+		assert_eq!(element_text_rgb("Pyro", Theme::Dark), element_rgb("pyro"));
+		assert_eq!(element_text_rgb("CRYO", Theme::Dark), (167, 223, 236));
+		// Pale colours get darker on light backgrounds
+		let (r, g, b) = element_text_rgb("Anemo", Theme::Light);
+		assert!(r < 148 && g < 255 && b < 198);
+	}
+}
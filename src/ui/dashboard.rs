@@ -0,0 +1,94 @@
+use eframe::egui;
+
+use super::{cover_uv, Assets, Palette};
+use crate::settings::Settings;
+use crate::utils;
+use crate::view::RosterView;
+
+pub fn show(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, view: &RosterView, settings: &Settings) {
+	if let Some(owner) = &view.owner {
+		let banner_height = 120.0;
+		let (banner_rect, _) = ui.allocate_exact_size(
+			egui::vec2(ui.available_width(), banner_height),
+			egui::Sense::hover(),
+		);
+
+		if let Some(name_card) = &owner.name_card {
+			assets.icons.ensure(name_card);
+			if let Some(card) = assets.icons.lock().get(name_card) {
+				if !card.loading && !card.error {
+					ui.painter().image(
+						card.texture.texture_id(ui.ctx()),
+						banner_rect,
+						cover_uv(card.texture.size_vec2(), banner_rect),
+						egui::Color32::WHITE,
+					);
+					ui.painter().rect_filled(banner_rect, 0.0, egui::Color32::from_black_alpha(120));
+				}
+			}
+		}
+
+		ui.allocate_ui_at_rect(banner_rect.shrink(12.0), |ui| {
+			ui.horizontal_centered(|ui| {
+				if let Some(picture) = &owner.profile_picture {
+					assets.icons.ensure(picture);
+					if let Some(icon) = assets.icons.lock().get(picture) {
+						icon.texture.show_size(ui, egui::vec2(80.0, 80.0));
+					}
+				}
+				ui.vertical(|ui| {
+					ui.heading(egui::RichText::new(&owner.nickname).size(28.0).strong().color(egui::Color32::WHITE));
+					ui.label(egui::RichText::new(format!(
+						"AR {} · {}",
+						owner.adventure_rank,
+						if owner.region.is_empty() { "Unknown region" } else { &owner.region }
+					)).color(egui::Color32::WHITE));
+					if owner.patreon {
+						ui.label(egui::RichText::new("Patreon supporter").color(egui::Color32::GOLD));
+					}
+				});
+			});
+		});
+	}
+
+	ui.add_space(10.0);
+
+	let summary = &view.summary;
+	egui::Grid::new("roster_summary")
+		.num_columns(2)
+		.spacing([20.0, 6.0])
+		.show(ui, |ui| {
+			ui.label("Characters");
+			ui.label(summary.character_count.to_string());
+			ui.end_row();
+
+			ui.label("Average crit value");
+			ui.label(utils::format_decimal(summary.average_crit_value, 2, settings.number_locale()));
+			ui.end_row();
+
+			for (label, percentile) in [("Best percentile", &summary.best), ("Worst percentile", &summary.worst)] {
+				ui.label(label);
+				match percentile {
+					Some(p) => ui.label(format!(
+						"{} · Top {:.2}% ({}/{}) · {}",
+						p.name, p.top_percent(), p.ranking, p.out_of, p.calculation
+					)),
+					None => ui.label("No leaderboard data"),
+				};
+				ui.end_row();
+			}
+		});
+
+	ui.add_space(10.0);
+	ui.heading(palette.heading("Elements"));
+	for (element, count) in &summary.elements {
+		ui.horizontal(|ui| {
+			ui.label(egui::RichText::new(element).color(palette.element(element)));
+			let fraction = *count as f32 / summary.character_count.max(1) as f32;
+			ui.add(egui::ProgressBar::new(fraction).desired_width(200.0).text(count.to_string()));
+		});
+	}
+
+	ui.add_space(10.0);
+	ui.label("Select a character from the list");
+}
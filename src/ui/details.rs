@@ -0,0 +1,881 @@
+use eframe::egui;
+use serde_json::Value;
+
+use super::{cover_uv, inspector, Assets, Palette};
+use crate::assets::CharacterAssets;
+use crate::history::{self, Snapshot};
+use crate::models;
+use crate::settings::Settings;
+use crate::state::{AppState, CalculationStatus, DetailTab, Event};
+use crate::view::{CharacterView, Compared, RosterView};
+use crate::{format_compact, format_decimal, stats, utils};
+
+fn render_constellations(ui: &mut egui::Ui, assets: &Assets, urls: &[String], constellation: i64, horizontal: bool) {
+	let icons = |ui: &mut egui::Ui| {
+		for cons_url in urls.iter().take(constellation.clamp(0, 6) as usize) {
+			assets.icons.ensure(cons_url);
+
+			if let Some(icon) = assets.icons.lock().get(cons_url) {
+				let size = 48.0;
+				icon.texture.show_size(ui, egui::vec2(size, size));
+			}
+			ui.add_space(4.0); // Small space between constellation icons
+		}
+	};
+	if horizontal {
+		ui.horizontal(icons);
+	} else {
+		ui.vertical(icons);
+	}
+}
+
+fn render_talent(ui: &mut egui::Ui, assets: &Assets, label: &str, talent: &Value, fallback_icon: Option<&str>) {
+	let icon_url = talent["icon"].as_str().or(fallback_icon).map(utils::icon_url);
+	if let Some(icon_url) = &icon_url {
+		assets.icons.ensure(icon_url);
+	}
+
+	let known = talent["level"].as_i64();
+	let level = known.unwrap_or(0);
+	let raw_level = talent["rawLevel"].as_i64().unwrap_or(level);
+	let boosted = talent["boosted"].as_bool().unwrap_or(level > raw_level);
+	// A crowned talent has its base level maxed out, constellation boosts aside
+	let crowned = raw_level >= 10;
+
+	ui.horizontal(|ui| {
+		let size = 32.0;
+		match &icon_url {
+			Some(icon_url) => {
+				if let Some(icon) = assets.icons.lock().get(icon_url) {
+					icon.texture.show_size(ui, egui::vec2(size, size));
+				}
+			}
+			// Keeps the labels lined up with the talents that have one
+			None => ui.add_space(size + ui.spacing().item_spacing.x),
+		}
+
+		let text = if known.is_none() {
+			format!("{}: –", label)
+		} else if boosted && level > raw_level {
+			format!("{}: {} (+{})", label, level, level.saturating_sub(raw_level))
+		} else {
+			format!("{}: {}", label, level)
+		};
+		let mut text = egui::RichText::new(text);
+		if crowned {
+			text = text.color(egui::Color32::GOLD).strong();
+		} else if boosted {
+			text = text.color(egui::Color32::LIGHT_BLUE);
+		}
+		let response = ui.label(text);
+		if boosted {
+			response.on_hover_text(format!("Base level {}, boosted by constellations", raw_level));
+		}
+
+		if crowned {
+			ui.label("👑").on_hover_text("Crowned");
+		}
+	});
+}
+
+// `None` shows a placeholder for a stat the build does not have
+fn render_stat_row(ui: &mut egui::Ui, assets: &Assets, locale: utils::NumberLocale, kind: stats::StatKind, value: Option<f64>) {
+	ui.horizontal(|ui| {
+		let size = 16.0;
+		if let Some(icon) = kind.icon() {
+			assets.stat_icons.get(icon).show_size(ui, egui::vec2(size, size));
+		} else {
+			ui.add_space(size + ui.spacing().item_spacing.x);
+		}
+		let value = value.map(|value| kind.format_in(value, locale)).unwrap_or_else(|| "–".to_string());
+		ui.label(format!("{} | {}", kind.name(), value));
+	});
+}
+
+fn render_name_card_banner(ui: &mut egui::Ui, assets: &Assets, char: &Value, rect: egui::Rect) {
+	let Some(name_card) = char["nameCardLink"].as_str() else {
+		return;
+	};
+	let banner_height = 52.0;
+	let banner_rect = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), banner_height));
+
+	assets.icons.ensure(name_card);
+	if let Some(card) = assets.icons.lock().get(&utils::icon_url(name_card)) {
+		if !card.loading && !card.error {
+			ui.painter().image(
+				card.texture.texture_id(ui.ctx()),
+				banner_rect,
+				cover_uv(card.texture.size_vec2(), banner_rect),
+				egui::Color32::WHITE,
+			);
+			ui.painter().rect_filled(banner_rect, 0.0, egui::Color32::from_black_alpha(100));
+		}
+	}
+
+	ui.allocate_ui_at_rect(banner_rect.shrink(6.0), |ui| {
+		ui.horizontal_centered(|ui| {
+			if let Some(picture) = char["profilePictureLink"].as_str() {
+				assets.icons.ensure(picture);
+				if let Some(icon) = assets.icons.lock().get(&utils::icon_url(picture)) {
+					icon.texture.show_size(ui, egui::vec2(40.0, 40.0));
+				}
+			}
+			if let Some(nickname) = char["owner"]["nickname"].as_str() {
+				ui.label(egui::RichText::new(nickname).size(18.0).strong().color(egui::Color32::WHITE));
+			}
+		});
+	});
+	ui.add_space(banner_height);
+}
+
+// Widths in points of the central panel
+const CARD_MIN_WIDTH: f32 = 260.0;
+const CONSTELLATION_WIDTH: f32 = 64.0;
+const SIDE_CONSTELLATIONS_MIN_WIDTH: f32 = 600.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DetailLayout {
+	pub card_columns: usize,
+	// Constellations get a column on the right, otherwise a row above the cards
+	pub side_constellations: bool,
+}
+
+pub fn detail_layout(width: f32) -> DetailLayout {
+	let side_constellations = width >= SIDE_CONSTELLATIONS_MIN_WIDTH;
+	let cards_width = if side_constellations {
+		width - CONSTELLATION_WIDTH
+	} else {
+		width
+	};
+	DetailLayout {
+		card_columns: ((cards_width / CARD_MIN_WIDTH) as usize).clamp(1, 4),
+		side_constellations,
+	}
+}
+
+pub fn show(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, state: &AppState, events: &mut Vec<Event>) {
+	let (Some(char), Some(view)) = (state.selected(), state.selected_view()) else {
+		return;
+	};
+	let calc = state.selected_calculation();
+	let settings = &state.settings;
+
+	let bg_url = &view.background_url;
+	assets.icons.ensure(bg_url);
+
+	let rect = ui.available_rect_before_wrap();
+
+	// Paint background if available
+	if let Some(bg) = assets.icons.lock().get(bg_url).filter(|bg| !bg.loading && !bg.error) {
+		let uv_rect = cover_uv(bg.texture.size_vec2(), rect);
+
+		ui.painter().image(
+			bg.texture.texture_id(ui.ctx()),
+			rect,
+			uv_rect,
+			egui::Color32::WHITE,
+		);
+		
+		// Add overlay
+		ui.painter().rect_filled(
+			rect,
+			0.0,
+			palette.overlay(),
+		);
+	}
+
+	// The equipped outfit instead of the default splash when we know its art
+	let icon_url = &view.assets.splash_url;
+	assets.icons.ensure(icon_url);
+
+	// Shown under the header, the banner is painted over the top of the panel
+	let mut art_status = None;
+	if state.detail_tab == DetailTab::Overview {
+		if let Some(icon) = assets.icons.lock().get(icon_url) {
+			if !icon.loading && !icon.error {
+				let uv_rect = cover_uv(icon.texture.size_vec2(), rect);
+
+				ui.painter().image(
+					icon.texture.texture_id(ui.ctx()),
+					rect,
+					uv_rect,
+					egui::Color32::WHITE,
+				);
+			} else if icon.loading {
+				art_status = Some((true, "Loading character art..."));
+			} else {
+				art_status = Some((false, "Failed to load character art"));
+			}
+		}
+	}
+
+	render_name_card_banner(ui, assets, char, rect);
+
+	// Labels in a wrapped row would flow around each other, so the ranking moves
+	// under the name as a whole when the panel is narrow
+	ui.horizontal_wrapped(|ui| {
+		ui.add(egui::Label::new(palette.heading(&view.name).heading().size(32.0).strong()).wrap(false));
+		if let Some(ranking) = &view.ranking_text {
+			ui.add_space(20.0);
+			ui.add(egui::Label::new(egui::RichText::new(ranking).heading().size(22.0).strong()).wrap(false));
+		}
+	});
+	if let Some(incomplete) = &view.incomplete {
+		ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", incomplete))
+			.on_hover_text("Missing parts of the build show as placeholders");
+	}
+	if let (Some(roster_view), Some(selected)) = (state.view(), state.selected_character) {
+		render_variants(ui, roster_view, view, selected, events);
+	}
+
+	ui.horizontal_wrapped(|ui| {
+		for (i, tab) in DetailTab::ALL.into_iter().enumerate() {
+			let response = ui
+				.selectable_label(state.detail_tab == tab, tab.name())
+				.on_hover_text(format!("Press {}", i + 1));
+			if response.clicked() {
+				events.push(Event::ShowTab(tab));
+			}
+		}
+	});
+	if let Some((loading, status)) = art_status {
+		ui.horizontal(|ui| {
+			if loading {
+				ui.spinner();
+			}
+			ui.weak(status);
+		});
+	}
+	ui.separator();
+
+	// Every tab has its own scroll position
+	egui::ScrollArea::vertical()
+		.id_source(state.detail_tab.name())
+		.auto_shrink([false; 2])
+		.show(ui, |ui| match state.detail_tab {
+			DetailTab::Overview => render_overview(ui, assets, palette, view, char, calc, settings),
+			DetailTab::Artifacts => render_artifacts(ui, assets, palette, char),
+			DetailTab::Weapon => render_weapon(ui, assets, palette, char, calc, settings.number_locale()),
+			DetailTab::Talents => render_talents(ui, assets, palette, char, &view.assets),
+			DetailTab::Calculations => {
+				let loading = matches!(state.calculations, CalculationStatus::Loading(_));
+				render_calculations(ui, palette, calc, loading, settings.number_locale())
+			}
+			DetailTab::Compare => render_compare(ui, palette, state, settings.number_locale()),
+			DetailTab::History if state.uid().is_none() => {
+				ui.weak("History is only kept for accounts loaded by UID");
+			}
+			DetailTab::History => render_history(ui, palette, state.history.as_deref(), char, settings.number_locale()),
+			DetailTab::RawData => inspector::show(ui, palette, state, char, calc, events),
+		});
+}
+
+fn render_overview(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, view: &CharacterView, char: &Value, calc: Option<&Value>, settings: &Settings) {
+	let constellation_urls = &view.assets.constellation_urls;
+	let constellation = char["constellation"].as_i64().unwrap_or(0);
+	let cards: [&dyn Fn(&mut egui::Ui); 3] = [
+		&|ui| render_info_card(ui, palette, char),
+		&|ui| render_stats_card(ui, assets, palette, char, settings),
+		&|ui| render_build_card(ui, assets, palette, char, calc, settings.number_locale()),
+	];
+
+	let layout = detail_layout(ui.available_width());
+	if layout.side_constellations {
+		ui.horizontal_top(|ui| {
+			let cards_width = ui.available_width() - CONSTELLATION_WIDTH;
+			ui.allocate_ui_with_layout(
+				egui::vec2(cards_width, ui.available_height()),
+				egui::Layout::top_down(egui::Align::Min),
+				|ui| render_cards(ui, &cards, layout.card_columns),
+			);
+			render_constellations(ui, assets, constellation_urls, constellation, false);
+		});
+	} else {
+		render_constellations(ui, assets, constellation_urls, constellation, true);
+		render_cards(ui, &cards, layout.card_columns);
+	}
+}
+
+// Cards fill the columns left to right, then wrap
+fn render_cards(ui: &mut egui::Ui, cards: &[&dyn Fn(&mut egui::Ui)], columns: usize) {
+	let columns = columns.min(cards.len()).max(1);
+	ui.columns(columns, |uis| {
+		for (i, card) in cards.iter().enumerate() {
+			egui::Frame::none()
+				.inner_margin(10.0)
+				.show(&mut uis[i % columns], |ui| {
+					ui.vertical(|ui| card(ui));
+				});
+		}
+	});
+}
+
+fn render_info_card(ui: &mut egui::Ui, palette: &Palette, char: &Value) {
+	ui.heading(palette.heading("Character Info"));
+	match utils::LevelInfo::character(char) {
+		Some(level) => render_level(ui, &level),
+		None => {
+			ui.weak("Level unknown");
+		}
+	}
+	ui.label(format!("Constellation: C{}", char["constellation"].as_i64().unwrap_or(0)));
+	if let Some(friendship) = char["fetterInfo"]["expLevel"].as_i64() {
+		ui.label(format!("Friendship: ❤ {}/10", friendship));
+	}
+}
+
+fn render_stats_card(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, char: &Value, settings: &Settings) {
+	let locale = settings.number_locale();
+	ui.heading(palette.heading("Stats"));
+
+	for kind in stats::CORE_STATS.iter().filter(|kind| settings.shows_stat(**kind)) {
+		render_stat_row(ui, assets, locale, *kind, stats::character_stat(char, *kind));
+	}
+
+	for kind in stats::BONUS_STATS.iter().filter(|kind| settings.shows_stat(**kind)) {
+		if let Some(value) = stats::character_stat(char, *kind) {
+			if value > 0.0 {
+				render_stat_row(ui, assets, locale, *kind, Some(value));
+			}
+		}
+	}
+}
+
+fn render_build_card(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, char: &Value, calc: Option<&Value>, locale: utils::NumberLocale) {
+	ui.heading(palette.heading("Build Quality"));
+	ui.horizontal(|ui| {
+		let size = 16.0;
+		assets.stat_icons.crit_dmg.show_size(ui, egui::vec2(size, size));
+		ui.label(format!("Crit Value | {:.2}", char["critValue"].as_f64().unwrap_or(0.0)));
+	});
+	let fit = calc.map(|calc| &calc["calculations"]["fit"]);
+	if let Some(result) = fit.and_then(|fit| fit["result"].as_f64()) {
+		ui.label(format!("Leaderboard result | {}", format_compact(result, locale)));
+	}
+}
+
+fn render_artifacts(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, char: &Value) {
+	ui.heading(palette.heading("Artifact Sets"));
+	if !char["artifactSets"].is_object() && !char["artifactObjects"].is_object() {
+		ui.weak("No artifacts in this build");
+		return;
+	}
+	if let Some(sets) = char["artifactSets"].as_object() {
+		for (name, details) in sets {
+			let label = format!("{} ({}pc)", name, details["count"].as_i64().unwrap_or(0));
+			match details.get("icon").and_then(|i| i.as_str()) {
+				Some(icon) => {
+					ui.horizontal(|ui| {
+						assets.icons.ensure(icon);
+						if let Some(icon) = assets.icons.lock().get(&utils::icon_url(icon)) {
+							icon.texture.show_size(ui, egui::vec2(32.0, 32.0));
+						}
+						ui.label(label);
+					});
+				}
+				None => {
+					ui.label(label);
+				}
+			}
+		}
+	}
+
+	if let Some(pieces) = char["artifactObjects"].as_object() {
+		ui.add_space(10.0);
+		ui.heading(palette.heading("Artifact Main Stats"));
+		for (piece, details) in pieces {
+			let key = details["mainStatKey"].as_str().unwrap_or("");
+			let stat_name = stats::StatKind::from_key(key)
+				.map(|kind| kind.name())
+				.unwrap_or(key);
+			ui.horizontal(|ui| {
+				if let Some(icon) = stats::StatKind::from_key(key).and_then(|kind| kind.icon()) {
+					assets.stat_icons.get(icon).show_size(ui, egui::vec2(16.0, 16.0));
+				}
+				ui.label(format!("{} | {}", utils::artifact_slot_name(piece), stat_name));
+			});
+		}
+	}
+}
+
+fn render_weapon(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, char: &Value, calc: Option<&Value>, locale: utils::NumberLocale) {
+	ui.heading(palette.heading("Weapon"));
+	// Indexing the `Value` rather than its map, missing keys are then just null
+	let weapon = &char["weapon"];
+	if !weapon.is_object() {
+		ui.weak("No weapon in this build");
+		return;
+	}
+	if let Some(icon) = weapon["icon"].as_str() {
+		assets.icons.ensure(icon);
+		if let Some(icon) = assets.icons.lock().get(&utils::icon_url(icon)) {
+			icon.texture.show_size(ui, egui::vec2(64.0, 64.0));
+		}
+	}
+	ui.label(format!(
+		"{} R{}",
+		weapon["name"].as_str().unwrap_or(""),
+		weapon["weaponInfo"]["refinementLevel"]["value"].as_i64().unwrap_or(0).saturating_add(1)
+	));
+	if let Some(level) = utils::LevelInfo::weapon(weapon) {
+		render_level(ui, &level);
+	}
+
+	for (kind, value) in models::weapon_stats(char, calc) {
+		render_stat_row(ui, assets, locale, kind, Some(value));
+	}
+
+	// The leaderboard knows the weapon's type and rarity, when it ranked the same weapon
+	let leaderboard_weapon = calc
+		.map(|calc| &calc["calculations"]["fit"]["weapon"])
+		.filter(|fit| fit["name"] == weapon["name"]);
+	if let Some(weapon) = leaderboard_weapon {
+		ui.add_space(10.0);
+		let rarity = weapon["rarity"].as_u64().unwrap_or(0).min(5) as usize;
+		ui.label(format!(
+			"{} {}",
+			weapon["type"].as_str().unwrap_or(""),
+			"★".repeat(rarity)
+		));
+		if let Some(substat) = weapon["substat"].as_str() {
+			ui.label(format!("Substat | {}", substat));
+		}
+	}
+}
+
+fn render_talents(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, char: &Value, character_assets: &CharacterAssets) {
+	ui.heading(palette.heading("Talents"));
+	// Only used when the payload has no talent icons
+	let normal_attack_icon = match char["weaponType"].as_str().unwrap_or("") {
+		"WEAPON_SWORD_ONE_HAND" => "Skill_A_01",
+		"WEAPON_BOW" => "Skill_A_02",
+		"WEAPON_POLE" => "Skill_A_03",
+		"WEAPON_CLAYMORE" => "Skill_A_04",
+		"WEAPON_CATALYST" => "Skill_A_Catalyst_MD",
+		_ => "Skill_A_01", // default to sword if unknown
+	};
+
+	let talents = [
+		("Normal Attack", "normalAttacks", Some(normal_attack_icon)),
+		("Elemental Skill", "elementalSkill", character_assets.skill_icon.as_deref()),
+		("Elemental Burst", "elementalBurst", character_assets.burst_icon.as_deref()),
+	];
+	for (label, key, fallback_icon) in talents {
+		render_talent(ui, assets, label, &char["talentsLevelMap"][key], fallback_icon);
+	}
+
+	ui.add_space(10.0);
+	ui.heading(palette.heading("Constellations"));
+	let constellation = char["constellation"].as_i64().unwrap_or(0);
+	if character_assets.constellation_urls.is_empty() {
+		ui.weak("No constellation art for this character");
+		return;
+	}
+	ui.horizontal_wrapped(|ui| {
+		for (i, cons_url) in (1..).zip(&character_assets.constellation_urls) {
+			assets.icons.ensure(cons_url);
+			let unlocked = i <= constellation;
+			ui.vertical(|ui| {
+				if let Some(icon) = assets.icons.lock().get(cons_url) {
+					// Locked ones are dimmed
+					let tint = if unlocked { egui::Color32::WHITE } else { egui::Color32::from_gray(70) };
+					ui.add(egui::Image::new(icon.texture.texture_id(ui.ctx()), egui::vec2(48.0, 48.0)).tint(tint));
+				}
+				let label = egui::RichText::new(format!("C{}", i));
+				ui.label(if unlocked { label.strong() } else { label.weak() });
+			});
+		}
+	});
+}
+
+fn render_calculations(ui: &mut egui::Ui, palette: &Palette, calc: Option<&Value>, loading: bool, locale: utils::NumberLocale) {
+	let calculations = calc
+		.and_then(|calc| calc["calculations"].as_object())
+		.filter(|calculations| !calculations.is_empty());
+	let Some(calculations) = calculations else {
+		if loading {
+			ui.horizontal(|ui| {
+				ui.spinner();
+				ui.label("Loading leaderboard calculations...");
+			});
+		} else {
+			ui.weak("This build has no leaderboard calculations");
+		}
+		return;
+	};
+
+	for calc in calculations.values() {
+		ui.heading(palette.heading(calc["name"].as_str().unwrap_or("Calculation")));
+		if let Some(details) = calc["details"].as_str() {
+			ui.weak(details);
+		}
+		if let Some(result) = calc["result"].as_f64() {
+			ui.label(format!("Result | {} ({})", format_compact(result, locale), format_decimal(result, 2, locale)));
+		}
+		if let (Some(ranking), Some(out_of)) = (calc["ranking"].as_i64(), calc["outOf"].as_i64()) {
+			ui.label(format!(
+				"Ranking | {} of {} (Top {}%)",
+				format_decimal(ranking as f64, 0, locale),
+				format_decimal(out_of as f64, 0, locale),
+				format_decimal(ranking as f64 / out_of.max(1) as f64 * 100.0, 2, locale)
+			));
+		}
+		if let Some(weapon) = calc["weapon"]["name"].as_str() {
+			ui.label(format!("Weapon | {} R{}", weapon, calc["weapon"]["refinement"].as_i64().unwrap_or(1)));
+		}
+		ui.add_space(10.0);
+	}
+}
+
+fn render_history(ui: &mut egui::Ui, palette: &Palette, snapshots: Option<&[Snapshot]>, char: &Value, locale: utils::NumberLocale) {
+	ui.heading(palette.heading("History"));
+	let Some(snapshots) = snapshots else {
+		ui.horizontal(|ui| {
+			ui.spinner();
+			ui.label("Loading history...");
+		});
+		return;
+	};
+	let entries = char["characterId"]
+		.as_i64()
+		.map(|id| history::character_history(snapshots, id, models::build_type(char)))
+		.unwrap_or_default();
+	if entries.is_empty() {
+		ui.weak("No snapshots of this character yet, every refresh that changes something adds one");
+		return;
+	}
+
+	egui::Grid::new("history_grid")
+		.num_columns(5)
+		.striped(true)
+		.spacing([20.0, 4.0])
+		.show(ui, |ui| {
+			for header in ["Date (UTC)", "Level", "Constellation", "Crit Value", "Ranking"] {
+				ui.strong(header);
+			}
+			ui.end_row();
+			for entry in entries {
+				ui.label(history::format_time(entry.time));
+				ui.label(entry.level.map(|level| level.to_string()).unwrap_or_default());
+				ui.label(format!("C{}", entry.constellation));
+				ui.label(format_decimal(entry.crit_value, 2, locale));
+				ui.label(
+					entry
+						.ranking
+						.map(|(ranking, out_of)| format!("{} / {}", format_decimal(ranking as f64, 0, locale), format_decimal(out_of as f64, 0, locale)))
+						.unwrap_or_default(),
+				);
+				ui.end_row();
+			}
+		});
+}
+
+// Switches between the current build and the saved ones, the tab stays open
+fn render_variants(ui: &mut egui::Ui, roster_view: &RosterView, view: &CharacterView, selected: usize, events: &mut Vec<Event>) {
+	if view.variants.len() < 2 {
+		return;
+	}
+	ui.horizontal_wrapped(|ui| {
+		ui.label("Build:");
+		for &variant in &view.variants {
+			if ui.selectable_label(variant == selected, roster_view.characters[variant].variant_name()).clicked() {
+				events.push(Event::Select(Some(variant)));
+			}
+		}
+	});
+}
+
+fn format_compared(compared: Compared, value: f64, locale: utils::NumberLocale) -> String {
+	match compared {
+		Compared::CritValue => format_decimal(value, 2, locale),
+		Compared::Stat(kind) => kind.format_in(value, locale),
+		Compared::Ranking => format_decimal(value, 0, locale),
+	}
+}
+
+fn render_compare(ui: &mut egui::Ui, palette: &Palette, state: &AppState, locale: utils::NumberLocale) {
+	ui.heading(palette.heading("Compare Builds"));
+	let (Some(view), Some(roster), Some(selected)) = (state.view(), state.roster(), state.selected_character) else {
+		return;
+	};
+	let variants = &view.characters[selected].variants;
+	if variants.len() < 2 {
+		ui.weak("Only the current build of this character is saved");
+		return;
+	}
+
+	// Every variant next to the current build, with how far it is ahead or behind
+	egui::Grid::new("compare_grid")
+		.num_columns(variants.len() + 1)
+		.striped(true)
+		.spacing([20.0, 4.0])
+		.show(ui, |ui| {
+			ui.label("");
+			for variant in variants {
+				let name = view.characters[*variant].variant_name();
+				if *variant == selected {
+					ui.strong(name);
+				} else {
+					ui.label(name);
+				}
+			}
+			ui.end_row();
+
+			for (compared, values) in view.compare(roster, selected) {
+				ui.label(compared.name());
+				let current = values[0];
+				for (column, value) in values.into_iter().enumerate() {
+					let Some(value) = value else {
+						ui.weak("—");
+						continue;
+					};
+					ui.horizontal(|ui| {
+						ui.label(format_compared(compared, value, locale));
+						let Some(current) = current.filter(|_| column > 0) else {
+							return;
+						};
+						let gain = compared.gain(value, current);
+						if gain != 0.0 {
+							let sign = if value > current { "+" } else { "−" };
+							let color = if gain > 0.0 { egui::Color32::GREEN } else { ui.visuals().error_fg_color };
+							let delta = format_compared(compared, (value - current).abs(), locale);
+							ui.colored_label(color, format!("{}{}", sign, delta));
+						}
+					});
+				}
+				ui.end_row();
+			}
+		});
+}
+
+fn render_level(ui: &mut egui::Ui, level: &utils::LevelInfo) {
+	ui.horizontal(|ui| {
+		ui.label(format!("Level {}/{}", level.level, level.cap));
+		ui.label(egui::RichText::new(utils::ascension_stars(level.ascension)).color(egui::Color32::GOLD))
+			.on_hover_text(format!("Ascension {}", level.ascension));
+	});
+	if level.needs_levelling() {
+		ui.colored_label(
+			egui::Color32::YELLOW,
+			format!("⬆ {} levels below cap", level.cap.saturating_sub(level.level)),
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image_cache::ImageCache;
+	use crate::test_support::{self, fixture_roster, FIXTURE_UID};
+
+	fn index_of(name: &str) -> usize {
+		fixture_roster()
+			.characters
+			.iter()
+			.position(|c| c["name"] == name)
+			.unwrap_or_else(|| panic!("{} is not in the fixture", name))
+	}
+
+	// The fixture with one character open on `tab`, history already read back
+	fn viewing(index: usize, tab: DetailTab) -> AppState {
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(fixture_roster());
+		state.handle(Event::Select(Some(index)));
+		state.handle(Event::ShowTab(tab));
+		state.handle(Event::HistoryLoaded {
+			uid: FIXTURE_UID.to_string(),
+			snapshots: vec![Snapshot { time: 1_714_566_600, roster: fixture_roster() }],
+		});
+		state
+	}
+
+	fn render_details(state: &AppState, width: f32, height: f32) -> egui::FullOutput {
+		let assets = Assets::new(ImageCache::offline());
+		test_support::render_headless(egui::vec2(width, height), |ctx| {
+			let palette = Palette::new(&state.settings, state.selected_view());
+			egui::CentralPanel::default().show(ctx, |ui| show(ui, &assets, &palette, state, &mut Vec::new()));
+		})
+	}
+
+	#[test]
+	fn detail_view_layout() {
+		let output = render_details(&viewing(index_of("Furina"), DetailTab::Overview), 1280.0, 720.0);
+		test_support::assert_snapshot("detail_furina_1280x720", &test_support::layout_snapshot(&output));
+	}
+
+	#[test]
+	fn detail_view_layout_large_window() {
+		let output = render_details(&viewing(index_of("Furina"), DetailTab::Overview), 1920.0, 1080.0);
+		test_support::assert_snapshot("detail_furina_1920x1080", &test_support::layout_snapshot(&output));
+	}
+
+	#[test]
+	fn detail_view_layout_boosted_talents() {
+		let output = render_details(&viewing(index_of("Shikanoin Heizou"), DetailTab::Talents), 1280.0, 720.0);
+		test_support::assert_snapshot("detail_heizou_1280x720", &test_support::layout_snapshot(&output));
+	}
+
+	#[test]
+	fn every_tab_renders_every_character() {
+		let characters = fixture_roster().characters.len();
+		for tab in DetailTab::ALL {
+			for index in 0..characters {
+				let output = render_details(&viewing(index, tab), 1280.0, 720.0);
+				let texts = test_support::painted_text(&output);
+				for name in DetailTab::ALL.map(DetailTab::name) {
+					assert!(texts.iter().any(|(text, _)| text == name), "{:?} of character {} misses {}", tab, index, name);
+				}
+			}
+		}
+
+		let output = render_details(&viewing(index_of("Furina"), DetailTab::History), 1280.0, 720.0);
+		let texts = test_support::painted_text(&output);
+		assert!(texts.iter().any(|(text, _)| text == "2024-05-01 12:30"));
+	}
+
+	#[test]
+	fn artifact_and_weapon_icons_are_requested_when_shown() {
+		let state = viewing(index_of("Furina"), DetailTab::Artifacts);
+		let char = state.selected().unwrap().clone();
+		let assets = Assets::new(ImageCache::offline());
+		let render = |state: &AppState| {
+			test_support::render_headless(egui::vec2(1280.0, 720.0), |ctx| {
+				let palette = Palette::new(&state.settings, state.selected_view());
+				egui::CentralPanel::default().show(ctx, |ui| show(ui, &assets, &palette, state, &mut Vec::new()));
+			});
+		};
+
+		// Nothing preloaded them, e.g. they were evicted since
+		render(&state);
+		for (_, set) in char["artifactSets"].as_object().unwrap() {
+			assert!(assets.icons.lock().contains_key(&utils::icon_url(set["icon"].as_str().unwrap())));
+		}
+		let mut state = state;
+		state.handle(Event::ShowTab(DetailTab::Weapon));
+		render(&state);
+		assert!(assets.icons.lock().contains_key(&utils::icon_url(char["weapon"]["icon"].as_str().unwrap())));
+	}
+
+	#[test]
+	fn calculation_only_characters_show_the_leaderboard() {
+		let mut roster = fixture_roster();
+		roster.characters.retain(|char| char["name"] != "Xiangling");
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(roster);
+		state.handle(Event::Select(Some(14)));
+
+		let texts_of = |state: &AppState| -> Vec<String> {
+			let output = render_details(state, 1280.0, 720.0);
+			test_support::painted_text(&output).into_iter().map(|(text, _)| text).collect()
+		};
+		let texts = texts_of(&state);
+		assert!(texts.iter().any(|text| text == "⚠ Only on the leaderboard, full stats are unavailable"));
+		assert!(texts.iter().any(|text| text.starts_with("Top ")));
+
+		state.handle(Event::ShowTab(DetailTab::Weapon));
+		let texts = texts_of(&state);
+		assert!(texts.iter().any(|text| text == "\"The Catch\" R5"));
+		assert!(texts.iter().any(|text| text == "Polearm ★★★★"));
+
+		state.handle(Event::ShowTab(DetailTab::RawData));
+		assert!(texts_of(&state).iter().any(|text| text == "No build entry, only the leaderboard calculation"));
+	}
+
+	#[test]
+	fn saved_builds_can_be_switched_and_compared() {
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(test_support::variant_roster("Furina", "Saved"));
+		state.handle(Event::Select(Some(0)));
+		state.handle(Event::ShowTab(DetailTab::Compare));
+
+		let output = render_details(&state, 1280.0, 720.0);
+		let texts: Vec<String> = test_support::painted_text(&output).into_iter().map(|(text, _)| text).collect();
+		for text in ["Build:", "Current", "Saved", "Crit Value", "Ranking", "150.00", "+100"] {
+			assert!(texts.iter().any(|t| t == text), "{} missing from {:?}", text, texts);
+		}
+		// The saved build is behind on crit value
+		assert!(texts.iter().any(|t| t.starts_with('−')));
+
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(fixture_roster());
+		state.handle(Event::Select(Some(0)));
+		state.handle(Event::ShowTab(DetailTab::Compare));
+		let output = render_details(&state, 1280.0, 720.0);
+		let texts: Vec<String> = test_support::painted_text(&output).into_iter().map(|(text, _)| text).collect();
+		assert!(texts.iter().any(|t| t == "Only the current build of this character is saved"));
+		assert!(!texts.iter().any(|t| t == "Build:"));
+	}
+
+	#[test]
+	fn history_waits_for_snapshots() {
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(fixture_roster());
+		state.handle(Event::Select(Some(0)));
+		state.handle(Event::ShowTab(DetailTab::History));
+		let texts = test_support::painted_text(&render_details(&state, 1280.0, 720.0));
+		assert!(texts.iter().any(|(text, _)| text == "Loading history..."));
+	}
+
+	#[test]
+	fn layout_reflows_by_width() {
+		let layout = |card_columns, side_constellations| DetailLayout {
+			card_columns,
+			side_constellations,
+		};
+		assert_eq!(detail_layout(300.0), layout(1, false));
+		assert_eq!(detail_layout(560.0), layout(2, false));
+		assert_eq!(detail_layout(600.0), layout(2, true));
+		assert_eq!(detail_layout(1080.0), layout(3, true));
+		assert_eq!(detail_layout(1720.0), layout(4, true));
+		assert_eq!(detail_layout(3640.0), layout(4, true));
+	}
+
+	#[test]
+	fn nothing_overlaps_from_small_windows_to_4k() {
+		let roster = fixture_roster();
+		for (width, height) in [(480.0, 800.0), (800.0, 600.0), (1280.0, 720.0), (1920.0, 1080.0), (3840.0, 2160.0)] {
+			let screen = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width, height));
+			for (index, char) in roster.characters.iter().enumerate() {
+				let name = char["name"].as_str().unwrap();
+				let state = viewing(index, DetailTab::Overview);
+				let texts: Vec<_> = test_support::painted_text(&render_details(&state, width, height))
+					.into_iter()
+					.filter(|(_, rect)| rect.intersects(screen))
+					.collect();
+
+				for (i, (text, rect)) in texts.iter().enumerate() {
+					assert!(rect.max.x <= width, "{} at {}x{}: {:?} runs off the panel", name, width, height, text);
+					for (other, other_rect) in &texts[i + 1..] {
+						let overlap = rect.intersect(*other_rect);
+						assert!(
+							overlap.width() <= 0.5 || overlap.height() <= 0.5,
+							"{} at {}x{}: {:?} overlaps {:?}",
+							name,
+							width,
+							height,
+							text,
+							other
+						);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn detail_overlays_stay_on_screen() {
+		let roster = fixture_roster();
+		let screen = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1280.0, 720.0));
+		for (index, char) in roster.characters.iter().enumerate() {
+			let name = char["name"].as_str().unwrap();
+			let output = render_details(&viewing(index, DetailTab::Overview), screen.width(), screen.height());
+			let texts = test_support::painted_text(&output);
+
+			let (_, name_rect) = texts.iter().find(|(text, _)| *text == name).expect("name is painted");
+			assert!(screen.contains_rect(*name_rect), "{}: name at {:?} is off screen", name, name_rect);
+			if let Some((_, rank_rect)) = texts.iter().find(|(text, _)| text.starts_with("Top ")) {
+				assert!(screen.contains_rect(*rank_rect), "{}: ranking at {:?} is off screen", name, rank_rect);
+			}
+		}
+	}
+}
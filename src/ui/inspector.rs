@@ -0,0 +1,154 @@
+use eframe::egui;
+use serde_json::Value;
+
+use super::Palette;
+use crate::inspector::{self, BUILD_FIELDS, CALCULATION_FIELDS};
+use crate::state::{AppState, Event};
+
+// One collapsible JSON document
+struct Tree<'a> {
+	root: &'a Value,
+	// Paths the viewer reads, everything else is highlighted
+	fields: &'a [&'a str],
+	query: &'a str,
+	unread: egui::Color32,
+}
+
+pub fn show(ui: &mut egui::Ui, palette: &Palette, state: &AppState, char: &Value, calc: Option<&Value>, events: &mut Vec<Event>) {
+	let mut search = state.raw_search.clone();
+	let search_edit = egui::TextEdit::singleline(&mut search).hint_text("🔍 Search keys and values");
+	if ui.add(search_edit).changed() {
+		events.push(Event::SearchRaw(search));
+	}
+	let unread = ui.visuals().warn_fg_color;
+	ui.label(egui::RichText::new("Highlighted fields are not shown anywhere else").color(unread))
+		.on_hover_text("Right-click a field to copy its value or path");
+	ui.add_space(10.0);
+
+	let query = state.raw_search.trim();
+	// The stand-in build of a calculation-only character is not akasha's data
+	let build = Some(char).filter(|_| !state.selected_view().is_some_and(|view| view.calculation_only));
+	let sections = [
+		("Build", build, BUILD_FIELDS, "No build entry, only the leaderboard calculation"),
+		("Calculation", calc, CALCULATION_FIELDS, "No matching calculation"),
+	];
+	for (title, value, fields, missing) in sections {
+		let Some(root) = value else {
+			ui.heading(palette.heading(title));
+			ui.weak(missing);
+			continue;
+		};
+		let tree = Tree { root, fields, query, unread };
+		ui.push_id(title, |ui| tree.node(ui, &mut Vec::new(), title, root, query, events));
+	}
+}
+
+impl Tree<'_> {
+	// Children are filtered by `query`, until a key matches and shows all of its fields
+	fn node(&self, ui: &mut egui::Ui, path: &mut Vec<String>, key: &str, value: &Value, query: &str, events: &mut Vec<Event>) {
+		let color = (!inspector::is_consumed(path, self.fields)).then_some(self.unread);
+		let children: Vec<(String, &Value)> = match value {
+			Value::Object(map) => map.iter().map(|(key, value)| (key.clone(), value)).collect(),
+			Value::Array(items) => items.iter().enumerate().map(|(i, value)| (i.to_string(), value)).collect(),
+			_ => {
+				let text = egui::RichText::new(format!("{}: {}", key, value)).monospace();
+				let response = ui.add(egui::Label::new(with_color(text, color)).sense(egui::Sense::click()));
+				self.copy_menu(response, path, value, events);
+				return;
+			}
+		};
+
+		let count = match value {
+			Value::Array(_) => format!("[{}]", children.len()),
+			_ => format!("{{{}}}", children.len()),
+		};
+		let header = egui::RichText::new(format!("{} {}", key, count)).monospace();
+		let searching = !self.query.is_empty();
+		let query = if key.to_lowercase().contains(&query.to_lowercase()) { "" } else { query };
+		let response = egui::CollapsingHeader::new(with_color(header, color))
+			.id_source(&path)
+			.default_open(path.is_empty())
+			.open(searching.then_some(true))
+			.show(ui, |ui| {
+				for (child_key, child) in &children {
+					if !inspector::matches(child_key, child, query) {
+						continue;
+					}
+					path.push(child_key.clone());
+					self.node(ui, path, child_key, child, query, events);
+					path.pop();
+				}
+			});
+		self.copy_menu(response.header_response, path, value, events);
+	}
+
+	fn copy_menu(&self, response: egui::Response, path: &[String], value: &Value, events: &mut Vec<Event>) {
+		let path = inspector::path_text(self.root, path);
+		response.context_menu(|ui| {
+			if ui.button("Copy value").clicked() {
+				ui.output_mut(|output| output.copied_text = inspector::copy_text(value));
+				events.push(Event::Notice(format!("Copied the value of {}", path)));
+				ui.close_menu();
+			}
+			if ui.button("Copy JSON path").clicked() {
+				ui.output_mut(|output| output.copied_text = path.clone());
+				events.push(Event::Notice(format!("Copied {}", path)));
+				ui.close_menu();
+			}
+		});
+	}
+}
+
+fn with_color(text: egui::RichText, color: Option<egui::Color32>) -> egui::RichText {
+	match color {
+		Some(color) => text.color(color),
+		None => text,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::image_cache::ImageCache;
+	use crate::settings::Settings;
+	use crate::state::DetailTab;
+	use crate::test_support::{self, fixture_roster, FIXTURE_UID};
+	use crate::ui::{details, Assets};
+
+	fn raw_tab(search: &str) -> Vec<String> {
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(fixture_roster());
+		state.handle(Event::Select(Some(0)));
+		state.handle(Event::ShowTab(DetailTab::RawData));
+		state.handle(Event::SearchRaw(search.to_string()));
+
+		let assets = Assets::new(ImageCache::offline());
+		let output = test_support::render_headless(egui::vec2(1280.0, 4000.0), |ctx| {
+			// Searching opens the headers, without waiting for them to animate
+			let mut style = (*ctx.style()).clone();
+			style.animation_time = 0.0;
+			ctx.set_style(style);
+			let palette = Palette::new(&state.settings, state.selected_view());
+			egui::CentralPanel::default().show(ctx, |ui| details::show(ui, &assets, &palette, &state, &mut Vec::new()));
+		});
+		test_support::painted_text(&output).into_iter().map(|(text, _)| text).collect()
+	}
+
+	#[test]
+	fn shows_both_documents_collapsed() {
+		let texts = raw_tab("");
+		assert!(texts.iter().any(|text| text.starts_with("Build {")));
+		assert!(texts.iter().any(|text| text.starts_with("Calculation {")));
+		assert!(texts.iter().any(|text| text.starts_with("weapon {")));
+		assert!(!texts.iter().any(|text| text.starts_with("weaponInfo {")));
+	}
+
+	#[test]
+	fn search_opens_matching_fields() {
+		let texts = raw_tab("refinementlevel");
+		assert!(texts.iter().any(|text| text.starts_with("weaponInfo {")));
+		assert!(texts.iter().any(|text| text.starts_with("refinementLevel {")));
+		assert!(texts.iter().any(|text| text.starts_with("value: ")));
+		assert!(!texts.iter().any(|text| text.starts_with("critValue")));
+	}
+}
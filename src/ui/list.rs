@@ -0,0 +1,83 @@
+use std::sync::atomic::Ordering;
+
+use eframe::egui;
+
+use super::{Assets, Palette};
+use crate::state::{AppState, Event};
+use crate::view::RosterView;
+
+// Fits the character icon, every row has the same height so offscreen ones can be skipped
+const ROW_HEIGHT: f32 = 32.0;
+
+pub fn show(ui: &mut egui::Ui, assets: &Assets, palette: &Palette, state: &AppState, view: &RosterView, scroll_to_cursor: bool, events: &mut Vec<Event>) {
+	// Pre-load every icon of the roster once, when it arrives
+	if assets.preloaded.swap(view.id, Ordering::Relaxed) != view.id {
+		for char in &view.characters {
+			for icon in &char.icons {
+				assets.icons.ensure(icon);
+			}
+		}
+	}
+
+	let visible = state.visible_characters();
+	let row_step = ROW_HEIGHT + ui.spacing().item_spacing.y;
+	egui::ScrollArea::vertical().show_viewport(ui, |ui, viewport| {
+		ui.set_height((row_step * visible.len() as f32 - ui.spacing().item_spacing.y).max(0.0));
+		let top = ui.max_rect().top();
+
+		for (row, idx) in visible.into_iter().enumerate() {
+			let y = row as f32 * row_step;
+			let rect = egui::Rect::from_x_y_ranges(ui.max_rect().x_range(), top + y..=top + y + ROW_HEIGHT);
+			let is_cursor = state.list_cursor == Some(idx);
+			if is_cursor && scroll_to_cursor {
+				ui.scroll_to_rect(rect, None);
+			}
+			if y + ROW_HEIGHT < viewport.min.y || y > viewport.max.y {
+				continue;
+			}
+
+			let char = &view.characters[idx];
+			// Also while one of the character's saved builds is open
+			let is_selected = state.selected_character.map(|selected| view.primary(selected)) == Some(idx);
+			ui.allocate_ui_at_rect(rect, |ui| {
+				ui.horizontal(|ui| {
+					// The cache may have evicted it since the preload
+					if let Some(icon_url) = &char.icon_url {
+						assets.icons.ensure(icon_url);
+						if let Some(icon) = assets.icons.lock().get(icon_url) {
+							icon.texture.show_size(ui, egui::vec2(ROW_HEIGHT, ROW_HEIGHT));
+						}
+					}
+
+					let mut response = ui.selectable_label(
+						is_selected,
+						egui::RichText::new(&char.name).color(palette.character(char)),
+					);
+					if is_cursor {
+						response = response.highlight();
+					}
+					if response.clicked() {
+						events.push(Event::Select(Some(idx)));
+					}
+
+					// The current build counts too
+					if char.variants.len() > 1 {
+						ui.weak(format!("×{}", char.variants.len()))
+							.on_hover_text(format!("{} builds", char.variants.len()));
+					}
+
+					if let Some(incomplete) = &char.incomplete {
+						ui.colored_label(ui.visuals().warn_fg_color, "⚠").on_hover_text(incomplete);
+					}
+
+					if let Some(level) = &char.level {
+						if level.needs_levelling() {
+							ui.label(egui::RichText::new("⬆").color(egui::Color32::YELLOW))
+								.on_hover_text(format!("Level {}/{}", level.level, level.cap));
+						}
+					}
+				});
+			});
+		}
+	});
+}
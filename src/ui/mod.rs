@@ -0,0 +1,425 @@
+use std::sync::atomic::AtomicU64;
+
+use eframe::egui;
+use egui_extras::RetainedImage;
+
+use crate::image_cache::ImageCache;
+use crate::settings::Settings;
+use crate::state::{AppState, CalculationStatus, Event, Phase};
+use crate::{stats, uid};
+
+mod dashboard;
+pub mod details;
+mod inspector;
+mod list;
+mod open;
+mod settings;
+mod shortcuts;
+pub mod theme;
+
+use theme::Palette;
+
+pub struct StatIcons {
+	hp: RetainedImage,
+	atk: RetainedImage,
+	def: RetainedImage,
+	crit_rate: RetainedImage,
+	crit_dmg: RetainedImage,
+	er: RetainedImage,
+	em: RetainedImage,
+}
+
+impl StatIcons {
+	fn new() -> Self {
+		Self {
+			hp: stat_icon("hp_icon", include_bytes!("../../assets/icons/hp.png")),
+			atk: stat_icon("atk_icon", include_bytes!("../../assets/icons/atk.png")),
+			def: stat_icon("def_icon", include_bytes!("../../assets/icons/def.png")),
+			crit_rate: stat_icon("crit_rate_icon", include_bytes!("../../assets/icons/crit_rate.png")),
+			crit_dmg: stat_icon("crit_dmg_icon", include_bytes!("../../assets/icons/crit_dmg.png")),
+			er: stat_icon("er_icon", include_bytes!("../../assets/icons/er.png")),
+			em: stat_icon("em_icon", include_bytes!("../../assets/icons/em.png")),
+		}
+	}
+
+	pub fn get(&self, icon: stats::StatIcon) -> &RetainedImage {
+		match icon {
+			stats::StatIcon::Hp => &self.hp,
+			stats::StatIcon::Atk => &self.atk,
+			stats::StatIcon::Def => &self.def,
+			stats::StatIcon::CritRate => &self.crit_rate,
+			stats::StatIcon::CritDmg => &self.crit_dmg,
+			stats::StatIcon::Er => &self.er,
+			stats::StatIcon::Em => &self.em,
+		}
+	}
+}
+
+// A grey square stands in for an icon that does not decode
+fn stat_icon(name: &str, bytes: &[u8]) -> RetainedImage {
+	RetainedImage::from_image_bytes(name, bytes)
+		.unwrap_or_else(|_| RetainedImage::from_color_image(name, egui::ColorImage::new([16, 16], egui::Color32::GRAY)))
+}
+
+// Textures the views draw with. Not part of the app state, images come and go on their own.
+pub struct Assets {
+	pub icons: ImageCache,
+	pub stat_icons: StatIcons,
+	// `RosterView::id` whose icons were last queued for loading
+	pub preloaded: AtomicU64,
+}
+
+impl Assets {
+	pub fn new(icons: ImageCache) -> Self {
+		Self {
+			icons,
+			stat_icons: StatIcons::new(),
+			preloaded: AtomicU64::new(0),
+		}
+	}
+}
+
+// Draws one frame of `state` and returns what the user did
+pub fn show(ctx: &egui::Context, state: &AppState, assets: &Assets) -> Vec<Event> {
+	let mut events = Vec::new();
+
+	let palette = Palette::new(&state.settings, state.selected_view());
+	let visuals = palette.visuals();
+	if ctx.style().visuals != visuals {
+		ctx.set_visuals(visuals);
+	}
+
+	let cursor_moved = shortcuts::read(ctx, state, &mut events);
+	open::file_drop(ctx, &mut events);
+	open::menu_bar(ctx, state, &mut events);
+
+	if state.settings_open {
+		settings::show(ctx, &state.settings, &mut events);
+	}
+	if state.help_open {
+		shortcuts::help(ctx, &mut events);
+	}
+	if let Some(dialog) = &state.open_dialog {
+		open::dialog(ctx, dialog, &mut events);
+	}
+
+	if let Phase::NoUid { input, error } = &state.phase {
+		uid_dialog(ctx, input, error.as_deref(), &mut events);
+		return events;
+	}
+
+	// Builds still show without their leaderboard results
+	if let CalculationStatus::Failed(error) = &state.calculations {
+		egui::TopBottomPanel::top("calculations_warning").show(ctx, |ui| {
+			ui.horizontal_wrapped(|ui| {
+				ui.colored_label(ui.visuals().warn_fg_color, "⚠ Leaderboard calculations could not be loaded")
+					.on_hover_text(error);
+				if ui.button("Retry").clicked() {
+					events.push(Event::RetryCalculations);
+				}
+			});
+		});
+	}
+
+	egui::SidePanel::left("character_list")
+		.default_width(200.0)
+		.show(ctx, |ui| {
+			ui.vertical(|ui| {
+				ui.heading(palette.heading("Characters"));
+				if let Some(uid) = state.uid() {
+					ui.weak(format!("UID {}", uid));
+				}
+				let opened = match &state.phase {
+					Phase::Opened { files, .. } => Some(files),
+					_ => None,
+				};
+				if let Some(files) = opened {
+					let names: Vec<_> = files
+						.iter()
+						.map(|file| file.file_name().unwrap_or(file.as_os_str()).to_string_lossy())
+						.collect();
+					ui.weak(format!("From {}", names.join(", ")));
+				}
+				if let Some(notice) = &state.notice {
+					ui.label(notice);
+				}
+
+				if opened.is_none() {
+					ui.horizontal(|ui| {
+						if ui.button("🔄 Refresh").clicked() {
+							events.push(Event::Refresh);
+						}
+						if matches!(state.phase, Phase::Refreshing { .. }) {
+							ui.spinner();
+						} else if matches!(state.calculations, CalculationStatus::Loading(_)) {
+							ui.spinner().on_hover_text("Loading leaderboard calculations...");
+						}
+					});
+				}
+
+				ui.horizontal(|ui| {
+					if opened.is_some() {
+						if ui.button("✖ Close file").clicked() {
+							events.push(Event::CloseFile);
+						}
+					} else if ui.button("🚪 Logout").clicked() {
+						events.push(Event::Logout);
+					}
+					if ui.button("⚙ Settings").clicked() {
+						events.push(Event::ShowSettings(!state.settings_open));
+					}
+				});
+
+				if ui.selectable_label(state.selected_character.is_none(), "🏠 Overview").clicked() {
+					events.push(Event::Select(None));
+				}
+
+				let mut sort = state.settings.sort;
+				settings::sort_combo(ui, "list_sort", &mut sort);
+				if sort != state.settings.sort {
+					events.push(Event::UpdateSettings(Settings {
+						sort,
+						..state.settings.clone()
+					}));
+				}
+
+				let mut search = state.search.clone();
+				let search_edit = egui::TextEdit::singleline(&mut search)
+					.id(egui::Id::new(shortcuts::SEARCH_ID))
+					.hint_text("🔍 Search (Ctrl+F)");
+				if ui.add(search_edit).changed() {
+					events.push(Event::Search(search));
+				}
+
+				ui.separator();
+
+				match &state.phase {
+					Phase::Loading { .. } => {
+						ui.spinner();
+					}
+					Phase::Error { message, .. } => {
+						ui.colored_label(egui::Color32::RED, message);
+					}
+					_ => {
+						if let Some(view) = state.view() {
+							list::show(ui, assets, &palette, state, view, cursor_moved, &mut events);
+						}
+					}
+				}
+			});
+		});
+
+	egui::CentralPanel::default().show(ctx, |ui| match &state.phase {
+		Phase::Loading { .. } => {
+			ui.spinner();
+			ui.label("Loading character data...");
+		}
+		Phase::Error { message, .. } => {
+			ui.colored_label(egui::Color32::RED, message);
+		}
+		_ => match (state.view(), state.selected()) {
+			(Some(_), Some(_)) => details::show(ui, assets, &palette, state, &mut events),
+			(Some(view), None) => dashboard::show(ui, assets, &palette, view, &state.settings),
+			_ => {}
+		},
+	});
+
+	events
+}
+
+fn uid_dialog(ctx: &egui::Context, input: &str, error: Option<&str>, events: &mut Vec<Event>) {
+	egui::CentralPanel::default().show(ctx, |ui| {
+		ui.vertical_centered(|ui| {
+			ui.add_space(100.0);
+			ui.heading("Enter your Genshin Impact UID");
+			ui.add_space(20.0);
+
+			let mut text = input.to_string();
+			let text_edit = ui.add(egui::TextEdit::singleline(&mut text)
+				.hint_text("Enter UID...")
+				.desired_width(200.0));
+
+			if text_edit.changed() {
+				events.push(Event::UidInput(text));
+			}
+
+			if text_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+				events.push(Event::SubmitUid);
+			}
+
+			ui.add_space(8.0);
+			if let Some(error) = error {
+				ui.colored_label(egui::Color32::RED, error);
+			} else if let Ok(region) = uid::validate(input) {
+				ui.weak(format!("Server: {}", region.name()));
+			}
+
+			ui.add_space(20.0);
+			ui.horizontal(|ui| {
+				if ui.button("📂 Open saved JSON").clicked() {
+					events.push(Event::ShowOpen(true));
+				}
+				if ui.button("⚙ Settings").clicked() {
+					events.push(Event::ShowSettings(true));
+				}
+			});
+		});
+	});
+}
+
+// UV rect that makes an image cover `rect` without stretching, cropping the overflow
+fn cover_uv(img_size: egui::Vec2, rect: egui::Rect) -> egui::Rect {
+	let img_aspect = img_size.x / img_size.y;
+	let rect_aspect = rect.width() / rect.height();
+
+	if img_aspect > rect_aspect {
+		// Image is wider than container - crop sides
+		let uv_width = rect_aspect / img_aspect;
+		let uv_x = (1.0 - uv_width) / 2.0;
+		egui::Rect::from_min_max(
+			egui::pos2(uv_x, 0.0),
+			egui::pos2(uv_x + uv_width, 1.0)
+		)
+	} else {
+		// Image is taller than container - crop top/bottom
+		let uv_height = img_aspect / rect_aspect;
+		let uv_y = (1.0 - uv_height) / 2.0;
+		egui::Rect::from_min_max(
+			egui::pos2(0.0, uv_y),
+			egui::pos2(1.0, uv_y + uv_height)
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::history::Snapshot;
+	use crate::models::Roster;
+	use crate::state::DetailTab;
+	use crate::test_support::{self, fixture_roster, FIXTURE_UID};
+	use proptest::prelude::*;
+	use serde_json::{json, Value};
+
+	// What a field is replaced with, `None` removes it
+	fn replacements() -> Vec<Option<Value>> {
+		vec![
+			None,
+			Some(Value::Null),
+			Some(json!("")),
+			Some(json!("é")),
+			Some(json!(-1)),
+			Some(json!(i64::MIN)),
+			Some(json!(i64::MAX)),
+			Some(json!(u64::MAX)),
+			Some(json!(1e300)),
+			Some(json!(-1e300)),
+			Some(json!({})),
+			Some(json!([])),
+			Some(json!(true)),
+		]
+	}
+
+	// JSON pointers to everything below `value`
+	fn pointers(value: &Value, prefix: &str, out: &mut Vec<String>) {
+		let children: Vec<(String, &Value)> = match value {
+			Value::Object(map) => map.iter().map(|(key, value)| (key.clone(), value)).collect(),
+			Value::Array(items) => items.iter().enumerate().map(|(i, value)| (i.to_string(), value)).collect(),
+			_ => return,
+		};
+		for (key, child) in children {
+			let pointer = format!("{}/{}", prefix, key.replace('~', "~0").replace('/', "~1"));
+			out.push(pointer.clone());
+			pointers(child, &pointer, out);
+		}
+	}
+
+	fn mutate(value: &mut Value, pointer: &str, replacement: Option<Value>) {
+		if let Some(replacement) = replacement {
+			if let Some(target) = value.pointer_mut(pointer) {
+				*target = replacement;
+			}
+			return;
+		}
+		let Some((parent, key)) = pointer.rsplit_once('/') else {
+			return;
+		};
+		let key = key.replace("~1", "/").replace("~0", "~");
+		match value.pointer_mut(parent) {
+			Some(Value::Object(map)) => {
+				map.remove(&key);
+			}
+			Some(Value::Array(items)) => {
+				if let Some(i) = key.parse().ok().filter(|i: &usize| *i < items.len()) {
+					items.remove(i);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	// Every view of the roster: the overview, then each tab of one character
+	fn render_everything(roster: Roster, character: usize) {
+		let snapshot = Roster::from_json(&roster.to_json()).unwrap();
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(roster);
+		state.handle(Event::HistoryLoaded {
+			uid: FIXTURE_UID.to_string(),
+			snapshots: vec![Snapshot { time: 0, roster: snapshot }],
+		});
+
+		let assets = Assets::new(ImageCache::offline());
+		let render = |state: &AppState| {
+			test_support::render_headless(egui::vec2(1280.0, 720.0), |ctx| {
+				show(ctx, state, &assets);
+			});
+		};
+		render(&state);
+		state.handle(Event::Select(Some(character)));
+		for tab in DetailTab::ALL {
+			state.handle(Event::ShowTab(tab));
+			render(&state);
+		}
+	}
+
+	proptest! {
+		#![proptest_config(ProptestConfig::with_cases(24))]
+
+		#[test]
+		fn mutated_builds_never_panic(
+			character in 0usize..15,
+			mutations in prop::collection::vec((any::<bool>(), any::<prop::sample::Index>(), 0usize..13), 1..12),
+		) {
+			let mut roster = fixture_roster();
+			let id = roster.characters[character]["characterId"].clone();
+			for (in_calculation, at, replacement) in mutations {
+				let calculation = roster
+					.calculations
+					.as_array_mut()
+					.and_then(|calcs| calcs.iter_mut().find(|calc| calc["characterId"] == id));
+				let target = match (in_calculation, calculation) {
+					(true, Some(calculation)) => calculation,
+					_ => &mut roster.characters[character],
+				};
+				let mut all = Vec::new();
+				pointers(target, "", &mut all);
+				if all.is_empty() {
+					continue;
+				}
+				let pointer = at.get(&all).clone();
+				mutate(target, &pointer, replacements()[replacement].clone());
+			}
+			render_everything(roster, character);
+		}
+	}
+
+	#[test]
+	fn empty_builds_still_render() {
+		let mut roster = fixture_roster();
+		roster.characters = vec![json!({}), Value::Null, json!({ "characterMetadata": { "element": "" } })];
+		roster.calculations = json!([{ "characterId": null, "calculations": { "fit": {} } }]);
+		for character in 0..3 {
+			render_everything(Roster::from_json(&roster.to_json()).unwrap(), character);
+		}
+	}
+}
@@ -0,0 +1,81 @@
+use eframe::egui;
+
+use crate::state::{AppState, Event, OpenDialog, Phase};
+
+pub fn menu_bar(ctx: &egui::Context, state: &AppState, events: &mut Vec<Event>) {
+	egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+		egui::menu::bar(ui, |ui| {
+			ui.menu_button("File", |ui| {
+				if ui.button("📂 Open… (Ctrl+O)").clicked() {
+					events.push(Event::ShowOpen(true));
+					ui.close_menu();
+				}
+				if matches!(state.phase, Phase::Opened { .. }) && ui.button("Close file").clicked() {
+					events.push(Event::CloseFile);
+					ui.close_menu();
+				}
+				if state.uid().is_some() && state.roster().is_some() && ui.button("Export (Ctrl+E)").clicked() {
+					events.push(Event::Export);
+					ui.close_menu();
+				}
+				ui.separator();
+				if ui.button("⚙ Settings").clicked() {
+					events.push(Event::ShowSettings(true));
+					ui.close_menu();
+				}
+			});
+		});
+	});
+}
+
+pub fn dialog(ctx: &egui::Context, dialog: &OpenDialog, events: &mut Vec<Event>) {
+	let mut open = true;
+	egui::Window::new("Open saved JSON")
+		.open(&mut open)
+		.resizable(false)
+		.collapsible(false)
+		.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+		.show(ctx, |ui| {
+			ui.label("Builds, calculations or both, one file per line. Files can also be dropped on the window.");
+			let mut input = dialog.input.clone();
+			let edit = egui::TextEdit::multiline(&mut input)
+				.hint_text("/path/to/builds.json\n/path/to/calculations.json")
+				.desired_rows(2)
+				.desired_width(400.0);
+			if ui.add(edit).changed() {
+				events.push(Event::OpenInput(input));
+			}
+			if let Some(error) = &dialog.error {
+				ui.colored_label(egui::Color32::RED, error);
+			}
+			if ui.button("Open").clicked() {
+				events.push(Event::SubmitOpen);
+			}
+		});
+	if !open {
+		events.push(Event::ShowOpen(false));
+	}
+}
+
+// Files dropped this frame are opened together, e.g. builds and calculations at once
+pub fn file_drop(ctx: &egui::Context, events: &mut Vec<Event>) {
+	let (hovering, dropped) = ctx.input(|input| {
+		let dropped: Vec<_> = input.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect();
+		(!input.raw.hovered_files.is_empty(), dropped)
+	});
+	if !dropped.is_empty() {
+		events.push(Event::OpenFiles(dropped));
+	}
+	if hovering {
+		let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("file_drop")));
+		let screen = ctx.screen_rect();
+		painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(180));
+		painter.text(
+			screen.center(),
+			egui::Align2::CENTER_CENTER,
+			"Drop saved akasha JSON to open it",
+			egui::FontId::proportional(24.0),
+			egui::Color32::WHITE,
+		);
+	}
+}
@@ -0,0 +1,225 @@
+use eframe::egui;
+
+use crate::models::SortOrder;
+use crate::settings::{Settings, Theme, LANGUAGES};
+use crate::state::Event;
+use crate::{stats, utils};
+
+// Keeps the slider's value in egui memory while it is dragged and only hands it
+// back once it is let go, so a drag is one change instead of one per frame
+fn apply_on_release<T: Clone + Send + Sync + 'static>(
+	ui: &mut egui::Ui,
+	id: &str,
+	value: &mut T,
+	add: impl FnOnce(&mut egui::Ui, &mut T) -> egui::Response,
+) {
+	let id = ui.id().with(id);
+	let mut draft = ui.data(|data| data.get_temp::<T>(id)).unwrap_or_else(|| value.clone());
+	if add(ui, &mut draft).dragged() {
+		ui.data_mut(|data| data.insert_temp(id, draft));
+	} else {
+		ui.data_mut(|data| data.remove::<T>(id));
+		*value = draft;
+	}
+}
+
+// Edits a copy of the settings, changes are sent back as they are made so they apply
+// live. Sliders and the URL apply once they are let go of.
+pub fn show(ctx: &egui::Context, settings: &Settings, events: &mut Vec<Event>) {
+	let mut open = true;
+	let mut edited = settings.clone();
+
+	egui::Window::new("Settings")
+		.open(&mut open)
+		.resizable(false)
+		.collapsible(false)
+		.show(ctx, |ui| {
+			egui::Grid::new("settings_grid")
+				.num_columns(2)
+				.spacing([20.0, 8.0])
+				.show(ui, |ui| {
+					ui.label("Theme");
+					ui.horizontal(|ui| {
+						for theme in Theme::ALL {
+							ui.selectable_value(&mut edited.theme, theme, theme.name());
+						}
+					});
+					ui.end_row();
+
+					ui.label("Element accent");
+					ui.checkbox(&mut edited.element_accent, "")
+						.on_hover_text("Tint selections and headings with the selected character's element");
+					ui.end_row();
+
+					ui.label("UI scale");
+					apply_on_release(ui, "settings_ui_scale", &mut edited.ui_scale, |ui, scale| {
+						ui.add(egui::Slider::new(scale, 0.5..=3.0).step_by(0.05))
+					});
+					ui.end_row();
+
+					ui.label("Language");
+					let language = LANGUAGES
+						.iter()
+						.find(|(code, _)| *code == edited.language)
+						.map(|(_, name)| *name)
+						.unwrap_or(edited.language.as_str())
+						.to_string();
+					egui::ComboBox::from_id_source("settings_language")
+						.selected_text(language)
+						.show_ui(ui, |ui| {
+							for (code, name) in LANGUAGES {
+								ui.selectable_value(&mut edited.language, code.to_string(), *name);
+							}
+						})
+						.response
+						.on_hover_text(format!("Numbers are shown as {}", edited.number_locale().name()));
+					ui.end_row();
+
+					ui.label("Image cache");
+					apply_on_release(ui, "settings_cache_limit", &mut edited.cache_limit_mb, |ui, limit| {
+						ui.add(egui::Slider::new(limit, 32..=2048).suffix(" MB"))
+					});
+					ui.end_row();
+
+					ui.label("API base URL");
+					api_url_field(ui, &mut edited.api_base_url);
+					ui.end_row();
+
+					ui.label("Auto refresh");
+					apply_on_release(ui, "settings_auto_refresh", &mut edited.auto_refresh_minutes, |ui, minutes| {
+						ui.add(egui::Slider::new(minutes, 0..=120).suffix(" min"))
+							.on_hover_text("0 turns auto refresh off")
+					});
+					ui.end_row();
+
+					ui.label("Sort characters by");
+					sort_combo(ui, "settings_sort", &mut edited.sort);
+					ui.end_row();
+				});
+
+			ui.separator();
+			ui.label("Shown stats");
+			egui::Grid::new("settings_stats").num_columns(3).show(ui, |ui| {
+				for (i, kind) in stats::CORE_STATS.iter().chain(stats::BONUS_STATS).enumerate() {
+					let mut shown = edited.shows_stat(*kind);
+					if ui.checkbox(&mut shown, kind.name()).changed() {
+						edited.set_stat_shown(*kind, shown);
+					}
+					if i % 3 == 2 {
+						ui.end_row();
+					}
+				}
+			});
+
+			ui.separator();
+			if ui.button("Reset to defaults").clicked() {
+				edited = Settings {
+					extra: settings.extra.clone(),
+					..Settings::default()
+				};
+			}
+		});
+
+	if !open {
+		events.push(Event::ShowSettings(false));
+	}
+	if edited != *settings {
+		events.push(Event::UpdateSettings(edited));
+	}
+}
+
+// Half-typed URLs stay in egui memory, the client only changes once the field is
+// left with a URL it can use
+fn api_url_field(ui: &mut egui::Ui, api_base_url: &mut Option<String>) {
+	let id = ui.id().with("settings_api_url");
+	let mut url = ui
+		.data(|data| data.get_temp::<String>(id))
+		.unwrap_or_else(|| api_base_url.clone().unwrap_or_default());
+	let response = ui.add(egui::TextEdit::singleline(&mut url).hint_text(utils::BASE_URL));
+
+	let valid = url.trim().is_empty() || utils::is_api_url(url.trim());
+	if response.has_focus() || !valid {
+		ui.data_mut(|data| data.insert_temp(id, url));
+	} else {
+		ui.data_mut(|data| data.remove::<String>(id));
+		if response.lost_focus() {
+			*api_base_url = Some(url.trim().to_string()).filter(|url| !url.is_empty());
+		}
+	}
+	if !valid {
+		ui.end_row();
+		ui.label("");
+		ui.colored_label(ui.visuals().error_fg_color, "Not an http(s) URL, the old one is still used");
+	}
+}
+
+pub fn sort_combo(ui: &mut egui::Ui, id: &str, sort: &mut SortOrder) {
+	egui::ComboBox::from_id_source(id)
+		.selected_text(sort.name())
+		.show_ui(ui, |ui| {
+			for order in SortOrder::ALL {
+				ui.selectable_value(sort, order, order.name());
+			}
+		});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support;
+
+	const LABELS: [&str; 8] = [
+		"Theme",
+		"Element accent",
+		"UI scale",
+		"Language",
+		"Image cache",
+		"API base URL",
+		"Auto refresh",
+		"Sort characters by",
+	];
+
+	#[test]
+	fn sliders_apply_once_released() {
+		let ctx = egui::Context::default();
+		let settings = Settings::default();
+		let run = |events: Vec<egui::Event>| {
+			let mut sent = Vec::new();
+			let input = egui::RawInput {
+				screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1280.0, 720.0))),
+				pixels_per_point: Some(1.0),
+				events,
+				..Default::default()
+			};
+			let output = ctx.run(input, |ctx| show(ctx, &settings, &mut sent));
+			(sent, output)
+		};
+		run(Vec::new());
+		let (_, output) = run(Vec::new());
+
+		// The slider rail starts the second column of the grid
+		let texts = test_support::painted_text(&output);
+		let label = |name: &str| texts.iter().find(|(text, _)| text == name).unwrap().1;
+		let column = LABELS.iter().map(|name| label(name).max.x).fold(0.0, f32::max) + 20.0;
+		let start = egui::pos2(column + 10.0, label("UI scale").center().y);
+		let end = start + egui::vec2(60.0, 0.0);
+		let button = |pos, pressed| egui::Event::PointerButton {
+			pos,
+			button: egui::PointerButton::Primary,
+			pressed,
+			modifiers: egui::Modifiers::NONE,
+		};
+
+		let (sent, _) = run(vec![egui::Event::PointerMoved(start), button(start, true)]);
+		assert!(sent.is_empty());
+		for x in [20.0, 40.0, 60.0] {
+			let (sent, _) = run(vec![egui::Event::PointerMoved(start + egui::vec2(x, 0.0))]);
+			assert!(sent.is_empty(), "applied while dragging");
+		}
+		let (sent, _) = run(vec![button(end, false)]);
+		match sent.as_slice() {
+			[Event::UpdateSettings(updated)] => assert!(updated.ui_scale != settings.ui_scale),
+			other => panic!("expected one update, got {}", other.len()),
+		}
+	}
+}
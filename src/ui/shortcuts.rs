@@ -0,0 +1,225 @@
+use eframe::egui::{self, Key, Modifiers};
+
+use crate::state::{AppState, DetailTab, Event};
+
+pub const SEARCH_ID: &str = "character_search";
+
+// (keys, what they do), listed by the "?" overlay
+pub const SHORTCUTS: &[(&str, &str)] = &[
+	("↑ / ↓", "Move through the character list"),
+	("Enter", "Open the highlighted character"),
+	("1 – 8", "Switch detail tabs"),
+	("Ctrl+F", "Search characters"),
+	("Ctrl+O", "Open saved JSON"),
+	("F5 / Ctrl+R", "Refresh"),
+	("Ctrl+E", "Export the build, or every build from the overview"),
+	("Esc", "Close overlays"),
+	("?", "Show this list"),
+];
+
+// Turns this frame's key presses into events. Runs before any widget so the list keys
+// are consumed here; returns whether the cursor moved so the list can scroll to it.
+pub fn read(ctx: &egui::Context, state: &AppState, events: &mut Vec<Event>) -> bool {
+	let search_id = egui::Id::new(SEARCH_ID);
+	let text_field = ctx.wants_keyboard_input();
+	// Typing into any other field (UID, settings) keeps its keys
+	let typing = text_field && !ctx.memory(|memory| memory.has_focus(search_id));
+	// Digits and "?" are text in the search field as well
+	let tab_keys = !text_field && state.selected().is_some();
+	let browsing = state.roster().is_some();
+	let mut moved = false;
+	let mut focus_search = false;
+
+	ctx.input_mut(|input| {
+		if input.key_pressed(Key::Escape) {
+			events.push(Event::CloseOverlay);
+		}
+		if !text_field && input.events.iter().any(|event| matches!(event, egui::Event::Text(text) if text == "?")) {
+			events.push(Event::ShowHelp(!state.help_open));
+		}
+		if input.consume_key(Modifiers::COMMAND, Key::O) {
+			events.push(Event::ShowOpen(true));
+		}
+		if !browsing {
+			return;
+		}
+
+		if input.consume_key(Modifiers::COMMAND, Key::F) {
+			focus_search = true;
+		}
+		if input.consume_key(Modifiers::NONE, Key::F5) || input.consume_key(Modifiers::COMMAND, Key::R) {
+			events.push(Event::Refresh);
+		}
+		if input.consume_key(Modifiers::COMMAND, Key::E) {
+			events.push(Event::Export);
+		}
+		if typing {
+			return;
+		}
+		for (key, delta) in [(Key::ArrowUp, -1), (Key::ArrowDown, 1)] {
+			if input.consume_key(Modifiers::NONE, key) {
+				events.push(Event::MoveCursor(delta));
+				moved = true;
+			}
+		}
+		if input.consume_key(Modifiers::NONE, Key::Enter) {
+			events.push(Event::OpenCursor);
+		}
+		if tab_keys {
+			let keys = [Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6, Key::Num7, Key::Num8];
+			for (key, tab) in keys.into_iter().zip(DetailTab::ALL) {
+				if input.consume_key(Modifiers::NONE, key) {
+					events.push(Event::ShowTab(tab));
+				}
+			}
+		}
+	});
+
+	if focus_search {
+		ctx.memory_mut(|memory| memory.request_focus(search_id));
+	}
+	moved
+}
+
+pub fn help(ctx: &egui::Context, events: &mut Vec<Event>) {
+	let mut open = true;
+	egui::Window::new("Keyboard shortcuts")
+		.open(&mut open)
+		.resizable(false)
+		.collapsible(false)
+		.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+		.show(ctx, |ui| {
+			egui::Grid::new("shortcuts_grid")
+				.num_columns(2)
+				.spacing([20.0, 6.0])
+				.show(ui, |ui| {
+					for (keys, action) in SHORTCUTS {
+						ui.strong(*keys);
+						ui.label(*action);
+						ui.end_row();
+					}
+				});
+		});
+	if !open {
+		events.push(Event::ShowHelp(false));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::settings::Settings;
+	use crate::test_support::{fixture_roster, FIXTURE_UID};
+
+	fn loaded() -> AppState {
+		let (mut state, _) = AppState::start(Some(FIXTURE_UID.to_string()), Settings::default());
+		state.show_cached(fixture_roster());
+		state
+	}
+
+	fn key(key: Key, modifiers: Modifiers) -> egui::Event {
+		egui::Event::Key {
+			key,
+			pressed: true,
+			repeat: false,
+			modifiers,
+		}
+	}
+
+	fn press(state: &AppState, input: Vec<egui::Event>) -> Vec<Event> {
+		let ctx = egui::Context::default();
+		let mut events = Vec::new();
+		let raw = egui::RawInput {
+			events: input,
+			..Default::default()
+		};
+		let _ = ctx.run(raw, |ctx| {
+			read(ctx, state, &mut events);
+		});
+		events
+	}
+
+	#[test]
+	fn keys_map_to_events() {
+		let state = loaded();
+		let events = press(
+			&state,
+			vec![
+				key(Key::ArrowDown, Modifiers::NONE),
+				key(Key::Enter, Modifiers::NONE),
+				key(Key::F5, Modifiers::NONE),
+				key(Key::E, Modifiers::COMMAND),
+				key(Key::Escape, Modifiers::NONE),
+				egui::Event::Text("?".to_string()),
+			],
+		);
+		assert!(matches!(
+			events.as_slice(),
+			[
+				Event::CloseOverlay,
+				Event::ShowHelp(true),
+				Event::Refresh,
+				Event::Export,
+				Event::MoveCursor(1),
+				Event::OpenCursor,
+			]
+		));
+	}
+
+	// Like `press`, with the search field on screen and focused
+	fn press_in_search(state: &AppState, input: Vec<egui::Event>) -> Vec<Event> {
+		let ctx = egui::Context::default();
+		let mut events = Vec::new();
+		let mut search = String::new();
+		let mut frame = |input: Vec<egui::Event>, events: &mut Vec<Event>| {
+			let raw = egui::RawInput {
+				events: input,
+				..Default::default()
+			};
+			let _ = ctx.run(raw, |ctx| {
+				read(ctx, state, events);
+				egui::CentralPanel::default().show(ctx, |ui| {
+					ui.add(egui::TextEdit::singleline(&mut search).id(egui::Id::new(SEARCH_ID)));
+				});
+			});
+		};
+		frame(Vec::new(), &mut Vec::new());
+		ctx.memory_mut(|memory| memory.request_focus(egui::Id::new(SEARCH_ID)));
+		frame(input, &mut events);
+		assert_eq!(search, "?", "the search field gets the text");
+		events
+	}
+
+	#[test]
+	fn question_mark_is_text_in_the_search_field() {
+		let state = loaded();
+		let events = press_in_search(
+			&state,
+			vec![egui::Event::Text("?".to_string()), key(Key::ArrowDown, Modifiers::NONE)],
+		);
+		assert!(matches!(events.as_slice(), [Event::MoveCursor(1)]));
+	}
+
+	#[test]
+	fn list_keys_need_a_roster() {
+		let (state, _) = AppState::start(None, Settings::default());
+		let events = press(
+			&state,
+			vec![key(Key::ArrowDown, Modifiers::NONE), key(Key::R, Modifiers::COMMAND), key(Key::O, Modifiers::COMMAND)],
+		);
+		assert!(matches!(events.as_slice(), [Event::ShowOpen(true)]));
+	}
+
+	#[test]
+	fn number_keys_switch_tabs() {
+		let mut state = loaded();
+		assert!(press(&state, vec![key(Key::Num3, Modifiers::NONE)]).is_empty());
+
+		state.handle(Event::Select(Some(0)));
+		let events = press(&state, vec![key(Key::Num3, Modifiers::NONE), key(Key::Num8, Modifiers::NONE)]);
+		assert!(matches!(
+			events.as_slice(),
+			[Event::ShowTab(DetailTab::Weapon), Event::ShowTab(DetailTab::RawData)]
+		));
+	}
+}
@@ -0,0 +1,131 @@
+use eframe::egui;
+
+use crate::settings::Settings;
+use crate::theme::{self, Rgb, Theme};
+use crate::view::CharacterView;
+
+fn color((r, g, b): Rgb) -> egui::Color32 {
+	egui::Color32::from_rgb(r, g, b)
+}
+
+// Colours for one frame: the theme plus the accent of the selected character, if enabled
+pub struct Palette {
+	theme: Theme,
+	accent: Option<egui::Color32>,
+}
+
+impl Palette {
+	pub fn new(settings: &Settings, selected: Option<&CharacterView>) -> Self {
+		let accent = selected
+			.filter(|_| settings.element_accent)
+			.map(|char| color(char.element_text(settings.theme)));
+		Self {
+			theme: settings.theme,
+			accent,
+		}
+	}
+
+	pub fn visuals(&self) -> egui::Visuals {
+		let mut visuals = match self.theme {
+			Theme::Dark => egui::Visuals::dark(),
+			Theme::Light => egui::Visuals::light(),
+			Theme::HighContrast => high_contrast(),
+		};
+		if let Some(accent) = self.accent {
+			visuals.selection.bg_fill = accent.gamma_multiply(0.45);
+			visuals.hyperlink_color = accent;
+			visuals.widgets.hovered.bg_stroke.color = accent;
+			visuals.widgets.active.bg_stroke.color = accent;
+			visuals.widgets.active.bg_fill = accent.gamma_multiply(0.6);
+		}
+		visuals
+	}
+
+	pub fn element(&self, element: &str) -> egui::Color32 {
+		color(theme::element_text_rgb(element, self.theme))
+	}
+
+	// Same as `element`, without working the colour out again
+	pub fn character(&self, char: &CharacterView) -> egui::Color32 {
+		color(char.element_text(self.theme))
+	}
+
+	pub fn heading(&self, text: impl Into<String>) -> egui::RichText {
+		let text = egui::RichText::new(text);
+		match self.accent {
+			Some(accent) => text.color(accent),
+			None => text,
+		}
+	}
+
+	// Dims artwork behind text so the theme's text colour stays readable
+	pub fn overlay(&self) -> egui::Color32 {
+		match self.theme {
+			Theme::Dark => egui::Color32::from_black_alpha(180),
+			Theme::Light => egui::Color32::from_white_alpha(200),
+			Theme::HighContrast => egui::Color32::from_black_alpha(230),
+		}
+	}
+}
+
+fn high_contrast() -> egui::Visuals {
+	let mut visuals = egui::Visuals::dark();
+	visuals.override_text_color = Some(egui::Color32::WHITE);
+	visuals.panel_fill = egui::Color32::BLACK;
+	visuals.window_fill = egui::Color32::BLACK;
+	visuals.extreme_bg_color = egui::Color32::BLACK;
+	visuals.faint_bg_color = egui::Color32::from_gray(24);
+	visuals.hyperlink_color = egui::Color32::from_rgb(255, 230, 0);
+	visuals.selection.bg_fill = egui::Color32::from_rgb(0, 70, 170);
+	visuals.selection.stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+
+	let widgets = &mut visuals.widgets;
+	for (widget, fill) in [
+		(&mut widgets.noninteractive, egui::Color32::BLACK),
+		(&mut widgets.inactive, egui::Color32::from_gray(30)),
+		(&mut widgets.hovered, egui::Color32::from_gray(60)),
+		(&mut widgets.active, egui::Color32::from_gray(80)),
+		(&mut widgets.open, egui::Color32::from_gray(45)),
+	] {
+		widget.bg_fill = fill;
+		widget.weak_bg_fill = fill;
+		widget.bg_stroke = egui::Stroke::new(1.0, egui::Color32::WHITE);
+		widget.fg_stroke = egui::Stroke::new(1.5, egui::Color32::WHITE);
+	}
+	visuals
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::fixture_roster;
+	use crate::view::RosterView;
+
+	#[test]
+	fn accent_follows_the_selected_element() {
+		let roster = fixture_roster();
+		let view = RosterView::new(&roster);
+		let char = &view.characters[0];
+		let element = roster.characters[0]["characterMetadata"]["element"].as_str().unwrap();
+
+		let mut settings = Settings::default();
+		assert!(Palette::new(&settings, Some(char)).accent.is_none());
+
+		settings.element_accent = true;
+		let palette = Palette::new(&settings, Some(char));
+		assert_eq!(palette.accent, Some(palette.element(element)));
+		assert_eq!(palette.visuals().hyperlink_color, palette.element(element));
+		assert_eq!(palette.character(char), palette.element(element));
+		assert!(Palette::new(&settings, None).accent.is_none());
+	}
+
+	#[test]
+	fn themes_pick_matching_visuals() {
+		let mut settings = Settings::default();
+		for theme in Theme::ALL {
+			settings.theme = theme;
+			let visuals = Palette::new(&settings, None).visuals();
+			assert_eq!(visuals.dark_mode, theme != Theme::Light, "{:?}", theme);
+		}
+	}
+}
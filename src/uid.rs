@@ -0,0 +1,105 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Region {
+	Asia,
+	Europe,
+	NorthAmerica,
+	TwHkMo,
+	China,
+}
+
+impl Region {
+	pub fn name(self) -> &'static str {
+		match self {
+			Region::Asia => "Asia",
+			Region::Europe => "Europe",
+			Region::NorthAmerica => "America",
+			Region::TwHkMo => "TW/HK/MO",
+			Region::China => "China",
+		}
+	}
+
+	// The server is encoded in the leading digit of a 9-digit UID
+	fn from_digit(digit: char) -> Option<Self> {
+		match digit {
+			'1'..='5' => Some(Region::China),
+			'6' => Some(Region::NorthAmerica),
+			'7' => Some(Region::Europe),
+			'8' => Some(Region::Asia),
+			'9' => Some(Region::TwHkMo),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum UidError {
+	Empty,
+	NotNumeric,
+	Length(usize),
+	UnknownRegion,
+}
+
+impl fmt::Display for UidError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			UidError::Empty => write!(f, "Enter a UID"),
+			UidError::NotNumeric => write!(f, "A UID only contains digits"),
+			UidError::Length(len) => write!(f, "A UID has 9 or 10 digits, this one has {}", len),
+			UidError::UnknownRegion => write!(f, "This UID does not belong to a known server"),
+		}
+	}
+}
+
+impl std::error::Error for UidError {}
+
+pub fn validate(uid: &str) -> Result<Region, UidError> {
+	let uid = uid.trim();
+	if uid.is_empty() {
+		return Err(UidError::Empty);
+	}
+	if !uid.chars().all(|c| c.is_ascii_digit()) {
+		return Err(UidError::NotNumeric);
+	}
+
+	let region_digit = match uid.len() {
+		9 => uid.chars().next(),
+		// 10-digit UIDs are the 9-digit ranges with a leading 1 (e.g. 18xxxxxxxx for Asia)
+		10 if uid.starts_with('1') => uid.chars().nth(1),
+		10 => None,
+		len => return Err(UidError::Length(len)),
+	};
+
+	region_digit
+		.and_then(Region::from_digit)
+		.ok_or(UidError::UnknownRegion)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn accepts_nine_and_ten_digit_uids() {
+		assert_eq!(validate("772493838"), Ok(Region::Europe));
+		assert_eq!(validate(" 618285856\n"), Ok(Region::NorthAmerica));
+		assert_eq!(validate("123456789"), Ok(Region::China));
+		assert_eq!(validate("1812345678"), Ok(Region::Asia));
+	}
+
+	#[test]
+	fn rejects_bad_input() {
+		assert_eq!(validate(""), Err(UidError::Empty));
+		assert_eq!(validate("   "), Err(UidError::Empty));
+		assert_eq!(validate("77249383a"), Err(UidError::NotNumeric));
+		assert_eq!(validate("-77249383"), Err(UidError::NotNumeric));
+		assert_eq!(validate("7724 9383"), Err(UidError::NotNumeric));
+		assert_eq!(validate("12345"), Err(UidError::Length(5)));
+		assert_eq!(validate("77249383"), Err(UidError::Length(8)));
+		assert_eq!(validate("77249383811"), Err(UidError::Length(11)));
+		assert_eq!(validate("072493838"), Err(UidError::UnknownRegion));
+		assert_eq!(validate("2812345678"), Err(UidError::UnknownRegion));
+		assert_eq!(validate("1012345678"), Err(UidError::UnknownRegion));
+	}
+}
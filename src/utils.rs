@@ -1,55 +1,544 @@
-use colored::Colorize;
 use reqwest;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::error::Error;
+use std::time::Duration;
 
-const BASE_URL: &str = "https://akasha.cv/api";
+pub const BASE_URL: &str = "https://akasha.cv/api";
 
-pub fn api_url(name: &str) -> String {
-    format!("{}{}", BASE_URL, name)
+// Builds come in pages, the current builds and every saved variant
+const BUILD_PAGE_SIZE: usize = 20;
+const MAX_BUILD_PAGES: usize = 10;
+
+#[derive(Clone)]
+pub struct ApiClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new(BASE_URL)
+    }
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_timeout(base_url, Duration::from_secs(30))
+    }
+
+    pub fn with_timeout(base_url: &str, timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        }
+    }
+
+    pub fn api_url(&self, name: &str) -> String {
+        format!("{}{}", self.base_url, name)
+    }
+
+    async fn get_data(&self, url: &str) -> Result<Value, Box<dyn Error>> {
+        let response = self.http.get(url).send().await?.error_for_status()?;
+        let data = response.json::<Value>().await?;
+        Ok(data["data"].clone())
+    }
+
+    pub async fn get_user_calculations(&self, user_id: &str) -> Result<Value, Box<dyn Error>> {
+        let url = self.api_url(&format!("/getCalculationsForUser/{}", user_id));
+        self.get_data(&url).await
+    }
+
+    // Every build type, read page by page until one comes back short, only repeats
+    // builds we already have, or `MAX_BUILD_PAGES` is reached. The flag is set in that
+    // last case, there may be more builds than were read.
+    pub async fn get_user_builds(&self, user_id: &str) -> Result<(Value, bool), Box<dyn Error>> {
+        let mut builds: Vec<Value> = Vec::new();
+        for page in 1..=MAX_BUILD_PAGES {
+            let url = self.api_url(&format!(
+                "/builds/?sort=critValue&order=-1&size={}&page={}&filter=&uids=&p=&fromId=&li=&uid={}",
+                BUILD_PAGE_SIZE, page, user_id
+            ));
+            let data = self.get_data(&url).await?;
+            let Some(entries) = data.as_array() else {
+                if page == 1 {
+                    return Ok((data, false));
+                }
+                return Ok((Value::Array(builds), false));
+            };
+
+            let before = builds.len();
+            for entry in entries {
+                let seen = entry["_id"].is_string() && builds.iter().any(|build| build["_id"] == entry["_id"]);
+                if !seen {
+                    builds.push(entry.clone());
+                }
+            }
+            if entries.len() < BUILD_PAGE_SIZE || builds.len() == before {
+                return Ok((Value::Array(builds), false));
+            }
+        }
+        Ok((Value::Array(builds), true))
+    }
+
+    pub async fn refresh_user(&self, user_id: &str) -> Result<(), String> {
+        let url = self.api_url(&format!("/user/refresh/{}", user_id));
+        self.http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+// Thousands and decimal separators. Stored in the settings, so variants are only ever added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberLocale {
+    #[default]
+    English,
+    German,
+    French,
+    Swiss,
+}
+
+impl NumberLocale {
+    pub const ALL: [NumberLocale; 4] = [
+        NumberLocale::English,
+        NumberLocale::German,
+        NumberLocale::French,
+        NumberLocale::Swiss,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberLocale::English => "1,234.5",
+            NumberLocale::German => "1.234,5",
+            NumberLocale::French => "1 234,5",
+            NumberLocale::Swiss => "1'234.5",
+        }
+    }
+
+    // (group, decimal)
+    pub fn separators(self) -> (char, char) {
+        match self {
+            NumberLocale::English => (',', '.'),
+            NumberLocale::German => ('.', ','),
+            // Narrow no-break space, so numbers never wrap
+            NumberLocale::French => ('\u{202f}', ','),
+            NumberLocale::Swiss => ('\'', '.'),
+        }
+    }
+}
+
+pub fn format_decimal(num: f64, decimals: usize, locale: NumberLocale) -> String {
+    if !num.is_finite() {
+        return num.to_string();
+    }
+    let (group, decimal) = locale.separators();
+    // Round first so carries (999.996 -> 1,000.00) are grouped correctly
+    let formatted = format!("{:.*}", decimals, num.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut out = String::with_capacity(formatted.len() + formatted.len() / 3 + 1);
+    // No "-0.00" when a tiny negative rounds away
+    if num < 0.0 && formatted.bytes().any(|b| matches!(b, b'1'..=b'9')) {
+        out.push('-');
+    }
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            out.push(group);
+        }
+        out.push(digit);
+    }
+    if let Some(frac_part) = frac_part {
+        out.push(decimal);
+        out.push_str(frac_part);
+    }
+    out
+}
+
+// Short form for big damage numbers: 20412 -> "20.4k", 1500000 -> "1.5M"
+pub fn format_compact(num: f64, locale: NumberLocale) -> String {
+    const UNITS: [&str; 5] = ["", "k", "M", "B", "T"];
+    if !num.is_finite() {
+        return num.to_string();
+    }
+
+    let rounds_to = |value: f64| format!("{:.1}", value).parse::<f64>().unwrap_or(value);
+    let mut scaled = num.abs();
+    let mut unit = 0;
+    while unit + 1 < UNITS.len() && rounds_to(scaled) >= 1000.0 {
+        scaled /= 1000.0;
+        unit += 1;
+    }
+
+    let (_, decimal) = locale.separators();
+    let mut digits = format_decimal(scaled, 1, locale);
+    if digits.ends_with(&format!("{}0", decimal)) {
+        digits.truncate(digits.len() - 2);
+    }
+    let sign = if num < 0.0 && digits != "0" { "-" } else { "" };
+    format!("{}{}{}", sign, digits, UNITS[unit])
 }
 
 pub fn format_number(num: f64) -> String {
-    let formatted = format!("{:.2}", num);
-    // Add commas for thousands
-    let parts: Vec<&str> = formatted.split('.').collect();
-    let mut int_part = parts[0].to_string();
-    let mut chars: Vec<char> = int_part.chars().rev().collect();
-    for i in (3..chars.len()).step_by(4) {
-        chars.insert(i, ',');
-    }
-    chars.reverse();
-    let with_commas: String = chars.into_iter().collect();
-    if parts.len() > 1 {
-        format!("{}.{}", with_commas, parts[1])
-    } else {
-        with_commas
+    format_decimal(num, 2, NumberLocale::English)
+}
+
+// Max level reachable at a given ascension phase. Characters use `propMap.ascension`,
+// weapons use `weaponInfo.promoteLevel`; both follow the same table.
+pub fn ascension_level_cap(ascension: i64) -> i64 {
+    match ascension {
+        i64::MIN..=0 => 20,
+        1 => 40,
+        2 => 50,
+        3 => 60,
+        4 => 70,
+        5 => 80,
+        _ => 90,
+    }
+}
+
+pub fn ascension_stars(ascension: i64) -> String {
+    let filled = ascension.clamp(0, 6) as usize;
+    format!("{}{}", "★".repeat(filled), "☆".repeat(6 - filled))
+}
+
+// `propMap` values arrive as strings ("90"), but accept plain numbers too
+pub fn prop_map_value(char: &Value, key: &str) -> Option<i64> {
+    let val = &char["propMap"][key]["val"];
+    val.as_i64()
+        .or_else(|| val.as_str().and_then(|s| s.parse().ok()))
+}
+
+#[derive(Debug)]
+pub struct LevelInfo {
+    pub level: i64,
+    pub ascension: i64,
+    pub cap: i64,
+}
+
+impl LevelInfo {
+    pub fn character(char: &Value) -> Option<Self> {
+        let level = prop_map_value(char, "level")?;
+        let ascension = prop_map_value(char, "ascension").unwrap_or(0);
+        Some(Self::new(level, ascension))
+    }
+
+    pub fn weapon(weapon: &Value) -> Option<Self> {
+        let level = weapon["weaponInfo"]["level"].as_i64()?;
+        let ascension = weapon["weaponInfo"]["promoteLevel"].as_i64().unwrap_or(0);
+        Some(Self::new(level, ascension))
+    }
+
+    fn new(level: i64, ascension: i64) -> Self {
+        Self {
+            level,
+            ascension,
+            cap: ascension_level_cap(ascension),
+        }
     }
+
+    pub fn needs_levelling(&self) -> bool {
+        self.level < self.cap
+    }
+}
+
+// Parts of a build the viewer needs that are missing or of the wrong type. The
+// build still renders, with placeholders, and is marked as incomplete.
+pub fn missing_fields(char: &Value) -> Vec<&'static str> {
+    let checks = [
+        ("name", char["name"].is_string()),
+        ("element", char["characterMetadata"]["element"].as_str().is_some_and(|element| !element.is_empty())),
+        ("level", LevelInfo::character(char).is_some()),
+        ("weapon", char["weapon"].is_object()),
+        ("talents", char["talentsLevelMap"].is_object()),
+        ("stats", char["stats"].is_object()),
+        ("artifacts", char["artifactSets"].is_object()),
+    ];
+    checks.into_iter().filter(|(_, ok)| !ok).map(|(name, _)| name).collect()
 }
 
-pub async fn get_user_calculations(user_id: &str) -> Result<Value, Box<dyn Error>> {
-    let url = api_url(&format!("/getCalculationsForUser/{}", user_id));
-    let response = reqwest::get(&url).await?;
-    let data = response.json::<Value>().await?;
-    Ok(data["data"].clone())
+// "pyro" -> "Pyro"
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn artifact_slot_name(piece: &str) -> &str {
+    match piece {
+        "EQUIP_BRACER" => "Flower",
+        "EQUIP_NECKLACE" => "Plume",
+        "EQUIP_SHOES" => "Sands",
+        "EQUIP_RING" => "Goblet",
+        "EQUIP_DRESS" => "Circlet",
+        _ => piece.trim_start_matches("EQUIP_"),
+    }
 }
 
-pub async fn get_user_builds(user_id: &str) -> Result<Value, Box<dyn Error>> {
-    let url = api_url(&format!(
-        "/builds/?sort=critValue&order=-1&size=20&page=1&filter=&uids=&p=&fromId=&li=&uid={}",
-        user_id
-    ));
-    let response = reqwest::get(&url).await?;
-    let data = response.json::<Value>().await?;
-    Ok(data["data"].clone())
+// Whether a configured API base URL can replace the default one
+pub fn is_api_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some_and(|host| !host.is_empty()))
 }
 
-pub async fn refresh_user(user_id: &str) -> Result<(), String> {
-    let url = api_url(&format!("/user/refresh/{}", user_id));
-    reqwest::get(&url)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+// Akasha mixes full URLs and bare enka asset names ("UI_AvatarIcon_Furina")
+pub fn icon_url(icon: &str) -> String {
+    if icon.starts_with("http") {
+        icon.to_string()
+    } else {
+        format!("https://enka.network/ui/{}.png", icon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockServer, Reply, FIXTURE_BUILDS, FIXTURE_UID};
+    use proptest::prelude::*;
+
+    // Undoes a locale's separators so the output can be compared numerically
+    fn parse(formatted: &str, locale: NumberLocale) -> f64 {
+        let (group, decimal) = locale.separators();
+        formatted
+            .replace(group, "")
+            .replace(decimal, ".")
+            .parse()
+            .unwrap_or_else(|_| panic!("{:?} does not parse", formatted))
+    }
+
+    fn any_locale() -> impl Strategy<Value = NumberLocale> {
+        prop::sample::select(NumberLocale::ALL.to_vec())
+    }
+
+    #[test]
+    fn formats_with_separators() {
+        assert_eq!(format_number(38550.625), "38,550.62");
+        assert_eq!(format_number(-123.0), "-123.00");
+        assert_eq!(format_number(-1234567.891), "-1,234,567.89");
+        assert_eq!(format_number(-0.001), "0.00");
+        assert_eq!(format_decimal(999.996, 2, NumberLocale::English), "1,000.00");
+        assert_eq!(format_decimal(38550.63, 0, NumberLocale::German), "38.551");
+        assert_eq!(format_decimal(38550.63, 1, NumberLocale::French), "38\u{202f}550,6");
+        assert_eq!(format_decimal(1234.5, 1, NumberLocale::Swiss), "1'234.5");
+    }
+
+    #[test]
+    fn formats_compact() {
+        let en = NumberLocale::English;
+        assert_eq!(format_compact(512.34, en), "512.3");
+        assert_eq!(format_compact(20412.0, en), "20.4k");
+        assert_eq!(format_compact(20000.0, en), "20k");
+        assert_eq!(format_compact(999_950.0, en), "1M");
+        assert_eq!(format_compact(-1_500_000.0, en), "-1.5M");
+        assert_eq!(format_compact(-0.01, en), "0");
+        assert_eq!(format_compact(2.5e9, NumberLocale::German), "2,5B");
+    }
+
+    #[test]
+    fn reports_missing_fields() {
+        let roster = crate::test_support::fixture_roster();
+        assert!(roster.characters.iter().all(|char| missing_fields(char).is_empty()));
+
+        let mut char = roster.characters[0].clone();
+        char["characterMetadata"]["element"] = "".into();
+        char["propMap"]["level"]["val"] = "ninety".into();
+        char.as_object_mut().unwrap().remove("weapon");
+        assert_eq!(missing_fields(&char), vec!["element", "level", "weapon"]);
+        assert_eq!(missing_fields(&Value::Null).len(), 7);
+
+        assert_eq!(capitalize("pyro"), "Pyro");
+        assert_eq!(capitalize("éclair"), "Éclair");
+        assert_eq!(capitalize(""), "");
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips_within_precision(num in -1e12f64..1e12, decimals in 0usize..4, locale in any_locale()) {
+            let formatted = format_decimal(num, decimals, locale);
+            let tolerance = 0.5 * 10f64.powi(-(decimals as i32)) + num.abs() * 1e-15;
+            prop_assert!((parse(&formatted, locale) - num).abs() <= tolerance, "{} -> {}", num, formatted);
+        }
+
+        #[test]
+        fn negatives_mirror_positives(num in 0f64..1e15, decimals in 0usize..4, locale in any_locale()) {
+            let positive = format_decimal(num, decimals, locale);
+            let negative = format_decimal(-num, decimals, locale);
+            if positive.chars().any(|c| matches!(c, '1'..='9')) {
+                prop_assert_eq!(negative, format!("-{}", positive));
+            } else {
+                prop_assert_eq!(negative, positive);
+            }
+        }
+
+        #[test]
+        fn groups_have_three_digits(num in -1e15f64..1e15, locale in any_locale()) {
+            let (group, _) = locale.separators();
+            let formatted = format_decimal(num, 0, locale);
+            let groups: Vec<&str> = formatted.trim_start_matches('-').split(group).collect();
+            prop_assert!((1..=3).contains(&groups[0].len()), "{}", formatted);
+            prop_assert!(groups[1..].iter().all(|g| g.len() == 3), "{}", formatted);
+        }
+
+        // T is the largest unit, so the range stops short of 1000T
+        #[test]
+        fn compact_stays_close(num in -999e12f64..999e12, locale in any_locale()) {
+            let formatted = format_compact(num, locale);
+            let (digits, scale) = match formatted.chars().last().unwrap() {
+                'k' => (&formatted[..formatted.len() - 1], 1e3),
+                'M' => (&formatted[..formatted.len() - 1], 1e6),
+                'B' => (&formatted[..formatted.len() - 1], 1e9),
+                'T' => (&formatted[..formatted.len() - 1], 1e12),
+                _ => (formatted.as_str(), 1.0),
+            };
+            let value = parse(digits, locale);
+            prop_assert!(value.abs() < 1000.0, "{}", formatted);
+            prop_assert!((value * scale - num).abs() <= 0.05 * scale + 1e-9, "{} -> {}", num, formatted);
+        }
+    }
+
+    #[test]
+    fn accepts_only_http_api_urls() {
+        assert!(is_api_url("https://akasha.cv/api"));
+        assert!(is_api_url("http://127.0.0.1:8080/api"));
+        assert!(!is_api_url("https://"));
+        assert!(!is_api_url("akasha.cv/api"));
+        assert!(!is_api_url("ftp://akasha.cv/api"));
+        assert!(!is_api_url("htt"));
+    }
+
+    #[tokio::test]
+    async fn fetches_builds() {
+        let server = MockServer::start();
+        let (builds, truncated) = server.client().get_user_builds(FIXTURE_UID).await.unwrap();
+
+        let builds = builds.as_array().unwrap();
+        assert!(!truncated);
+        assert_eq!(builds.len(), 15);
+        assert_eq!(builds[0]["name"], "Furina");
+        assert_eq!(server.hits("/api/builds/"), 1);
+    }
+
+    #[tokio::test]
+    async fn fetches_every_page_of_builds() {
+        let server = MockServer::start();
+        let fixture: Value = serde_json::from_str(FIXTURE_BUILDS).unwrap();
+        let current = fixture["data"].as_array().unwrap();
+        let saved = |index: usize| {
+            let mut build = current[index].clone();
+            build["_id"] = format!("saved-{}", index).into();
+            build["type"] = "Saved".into();
+            build
+        };
+
+        // A full first page, then a short one that repeats a build from the first
+        let first: Vec<Value> = current.iter().cloned().chain((0..5).map(saved)).collect();
+        let second = vec![saved(5), saved(6), current[0].clone()];
+        let page = |n: usize| format!("/api/builds/?sort=critValue&order=-1&size=20&page={}", n);
+        server.reply(&page(1), Reply::Json(serde_json::json!({ "data": first }).to_string()));
+        server.reply(&page(2), Reply::Json(serde_json::json!({ "data": second }).to_string()));
+
+        let (builds, truncated) = server.client().get_user_builds(FIXTURE_UID).await.unwrap();
+        let builds = builds.as_array().unwrap();
+        assert!(!truncated);
+        assert_eq!(builds.len(), 22);
+        assert_eq!(builds.iter().filter(|build| build["type"] == "Saved").count(), 7);
+        assert_eq!(server.hits("/api/builds/"), 2);
+    }
+
+    #[tokio::test]
+    async fn stops_after_the_last_full_page() {
+        let server = MockServer::start();
+        let fixture: Value = serde_json::from_str(FIXTURE_BUILDS).unwrap();
+        let build = &fixture["data"][0];
+        for page in 1..=MAX_BUILD_PAGES + 1 {
+            let entries: Vec<Value> = (0..BUILD_PAGE_SIZE)
+                .map(|index| {
+                    let mut build = build.clone();
+                    build["_id"] = format!("saved-{}-{}", page, index).into();
+                    build
+                })
+                .collect();
+            let url = format!("/api/builds/?sort=critValue&order=-1&size=20&page={}&", page);
+            server.reply(&url, Reply::Json(serde_json::json!({ "data": entries }).to_string()));
+        }
+
+        let (builds, truncated) = server.client().get_user_builds(FIXTURE_UID).await.unwrap();
+        assert!(truncated);
+        assert_eq!(builds.as_array().unwrap().len(), MAX_BUILD_PAGES * BUILD_PAGE_SIZE);
+        assert_eq!(server.hits("/api/builds/"), MAX_BUILD_PAGES);
+    }
+
+    #[tokio::test]
+    async fn fetches_calculations() {
+        let server = MockServer::start();
+        let calcs = server.client().get_user_calculations(FIXTURE_UID).await.unwrap();
+
+        let calcs = calcs.as_array().unwrap();
+        assert_eq!(calcs.len(), 7);
+        assert!(calcs.iter().any(|c| c["characterId"] == 10000023));
+        assert_eq!(server.hits(&format!("/api/getCalculationsForUser/{}", FIXTURE_UID)), 1);
+    }
+
+    #[tokio::test]
+    async fn refreshes_user() {
+        let server = MockServer::start();
+        server.client().refresh_user(FIXTURE_UID).await.unwrap();
+        assert_eq!(server.hits("/api/user/refresh/"), 1);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_reported() {
+        let server = MockServer::start();
+        server.reply("/api/builds/", Reply::Status(500));
+        server.reply("/api/user/refresh/", Reply::Status(503));
+
+        assert!(server.client().get_user_builds(FIXTURE_UID).await.is_err());
+        assert!(server.client().refresh_user(FIXTURE_UID).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_reported() {
+        let server = MockServer::start();
+        server.reply("/api/getCalculationsForUser/", Reply::Json("{\"data\": [".to_string()));
+
+        assert!(server.client().get_user_calculations(FIXTURE_UID).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn slow_responses_time_out() {
+        let server = MockServer::start();
+        server.reply(
+            "/api/builds/",
+            Reply::Slow(Duration::from_secs(5), Box::new(Reply::Json("{\"data\":[]}".to_string()))),
+        );
+        let client = ApiClient::with_timeout(&server.api_url(), Duration::from_millis(200));
+
+        assert!(client.get_user_builds(FIXTURE_UID).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_is_reported() {
+        let server = MockServer::start();
+        let url = server.api_url();
+        drop(server);
+        // Give the listener thread a moment to shut down
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let client = ApiClient::with_timeout(&url, Duration::from_millis(500));
+        assert!(client.get_user_builds(FIXTURE_UID).await.is_err());
+    }
 }
@@ -0,0 +1,443 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{json, Value};
+
+use crate::assets::CharacterAssets;
+use crate::models::{self, Roster, SortOrder};
+use crate::overview::{OwnerProfile, RosterSummary};
+use crate::stats::{self, StatKind};
+use crate::theme::{self, Rgb, Theme};
+use crate::utils::{self, LevelInfo};
+
+// What the list and the detail header show of one build, worked out once when a
+// roster arrives instead of on every frame
+#[derive(Debug)]
+pub struct CharacterView {
+	pub name: String,
+	// Lowercase, what the search matches against
+	search_name: String,
+	pub element: String,
+	// The element colour made readable on each theme, in `Theme::ALL` order
+	element_text: [Rgb; 3],
+	pub icon_url: Option<String>,
+	pub assets: CharacterAssets,
+	pub background_url: String,
+	// Index into the roster's calculations
+	pub calculation: Option<usize>,
+	pub ranking_text: Option<String>,
+	pub level: Option<LevelInfo>,
+	// Only on the leaderboard, the build is made up from the calculation
+	pub calculation_only: bool,
+	// The build's `type`, "current" or the name of a saved build
+	pub variant: String,
+	// Every build of this character, the current one first, this one included
+	pub variants: Vec<usize>,
+	// "Incomplete data, missing weapon, stats" for builds with missing fields
+	pub incomplete: Option<String>,
+	// Every icon anywhere in the build, fetched ahead of being shown
+	pub icons: Vec<String>,
+}
+
+impl CharacterView {
+	fn new(char: &Value, calculations: &HashMap<(i64, &str), usize>, roster: &Roster, calculation_only: bool) -> Self {
+		let name = char["name"].as_str().unwrap_or("Unknown").to_string();
+		let element = char["characterMetadata"]["element"].as_str().unwrap_or("").to_lowercase();
+		let variant = models::build_type(char);
+		let calculation = char["characterId"].as_i64().and_then(|id| calculations.get(&(id, variant)).copied());
+		let calc = calculation.and_then(|index| roster.calculations.get(index));
+
+		let missing = utils::missing_fields(char);
+		let incomplete = if calculation_only {
+			Some("Only on the leaderboard, full stats are unavailable".to_string())
+		} else {
+			(!missing.is_empty()).then(|| format!("Incomplete data, missing {}", missing.join(", ")))
+		};
+		let mut icons = Vec::new();
+		collect_icons(char, &mut icons);
+
+		Self {
+			search_name: name.to_lowercase(),
+			element_text: Theme::ALL.map(|theme| theme::element_text_rgb(&element, theme)),
+			icon_url: char["icon"].as_str().map(utils::icon_url),
+			assets: CharacterAssets::resolve(char),
+			background_url: format!("https://akasha.cv/elementalBackgrounds/{}-bg.jpg", utils::capitalize(&element)),
+			calculation,
+			ranking_text: calc.and_then(ranking_text),
+			level: LevelInfo::character(char),
+			calculation_only,
+			variant: variant.to_string(),
+			variants: Vec::new(),
+			incomplete,
+			icons,
+			name,
+			element,
+		}
+	}
+
+	pub fn variant_name(&self) -> &str {
+		match self.variant.as_str() {
+			"current" => "Current",
+			name => name,
+		}
+	}
+
+	pub fn element_text(&self, theme: Theme) -> Rgb {
+		let index = Theme::ALL.iter().position(|t| *t == theme).unwrap_or(0);
+		self.element_text[index]
+	}
+}
+
+// The views of a whole roster: `Roster::characters` first, then a stand-in build for
+// every build that only appears in the calculations. Saved variants have views of
+// their own but only the current build of each character is listed
+pub struct RosterView {
+	// Changes whenever a new roster arrives, so the UI knows when to preload again
+	pub id: u64,
+	pub characters: Vec<CharacterView>,
+	// The stand-in builds, indexed after the roster's own
+	extra: Vec<Value>,
+	// Indices of the listed builds in `SortOrder::ALL` order
+	orders: Vec<Vec<usize>>,
+	pub owner: Option<OwnerProfile>,
+	pub summary: RosterSummary,
+}
+
+impl RosterView {
+	pub fn new(roster: &Roster) -> Self {
+		static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+		// Keyed by character and build type, first entry wins like the linear search it replaces
+		let mut calculations = HashMap::new();
+		for (index, calc) in roster.calculations.as_array().into_iter().flatten().enumerate() {
+			if let Some(id) = calc["characterId"].as_i64() {
+				calculations.entry((id, models::build_type(calc))).or_insert(index);
+			}
+		}
+
+		// A union by character and build type, later calculations for the same build are ignored
+		let key = |build: &Value| Some((build["characterId"].as_i64()?, models::build_type(build).to_string()));
+		let mut known: HashSet<(i64, String)> = roster.characters.iter().filter_map(key).collect();
+		let extra: Vec<Value> = roster
+			.calculations
+			.as_array()
+			.into_iter()
+			.flatten()
+			.filter(|calc| key(calc).is_some_and(|key| known.insert(key)))
+			.map(calculation_build)
+			.collect();
+
+		let builds: Vec<&Value> = roster.characters.iter().chain(&extra).collect();
+		let mut characters: Vec<CharacterView> = builds
+			.iter()
+			.enumerate()
+			.map(|(index, char)| CharacterView::new(char, &calculations, roster, index >= roster.characters.len()))
+			.collect();
+
+		// Builds without a character id are never grouped
+		let mut groups: Vec<Vec<usize>> = Vec::new();
+		let mut group_of: HashMap<i64, usize> = HashMap::new();
+		for (index, char) in builds.iter().enumerate() {
+			match char["characterId"].as_i64() {
+				Some(id) => match group_of.get(&id) {
+					Some(group) => groups[*group].push(index),
+					None => {
+						group_of.insert(id, groups.len());
+						groups.push(vec![index]);
+					}
+				},
+				None => groups.push(vec![index]),
+			}
+		}
+		for mut group in groups {
+			// Stable, so saved builds keep the order they came in
+			group.sort_by_key(|index| characters[*index].variant != "current");
+			for index in &group {
+				characters[*index].variants = group.clone();
+			}
+		}
+
+		let orders = SortOrder::ALL
+			.iter()
+			.map(|sort| {
+				let mut order = models::sorted_indices(&builds, *sort);
+				order.retain(|index| characters[*index].variants.first() == Some(index));
+				order
+			})
+			.collect();
+		// Saved builds would count their character twice
+		let current: Vec<&Value> = builds
+			.iter()
+			.enumerate()
+			.filter(|(index, _)| characters[*index].variant == "current" && characters[*index].variants.first() == Some(index))
+			.map(|(_, build)| *build)
+			.collect();
+		let current_calculations: Vec<&Value> = roster
+			.calculations
+			.as_array()
+			.into_iter()
+			.flatten()
+			.filter(|calc| models::build_type(calc) == "current")
+			.collect();
+		let summary = RosterSummary::new(&current, &current_calculations);
+
+		Self {
+			id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+			characters,
+			extra,
+			orders,
+			owner: OwnerProfile::from_builds(&roster.characters),
+			summary,
+		}
+	}
+
+	pub fn sorted(&self, sort: SortOrder) -> &[usize] {
+		let index = SortOrder::ALL.iter().position(|s| *s == sort).unwrap_or(0);
+		&self.orders[index]
+	}
+
+	// The listed build of the character `index` is a variant of
+	pub fn primary(&self, index: usize) -> usize {
+		self.characters
+			.get(index)
+			.and_then(|view| view.variants.first().copied())
+			.unwrap_or(index)
+	}
+
+	// `query` is already lowercase
+	pub fn matches(&self, index: usize, query: &str) -> bool {
+		self.characters
+			.get(index)
+			.is_some_and(|view| view.search_name.contains(query))
+	}
+
+	// The roster's build, or the stand-in for a calculation-only character
+	pub fn build<'a>(&'a self, roster: &'a Roster, index: usize) -> Option<&'a Value> {
+		roster
+			.characters
+			.get(index)
+			.or_else(|| self.extra.get(index - roster.characters.len()))
+	}
+
+	pub fn calculation<'a>(&self, roster: &'a Roster, index: usize) -> Option<&'a Value> {
+		let calc = self.characters.get(index)?.calculation?;
+		roster.calculations.get(calc)
+	}
+
+	// Each compared number for every variant of the character at `index`, in
+	// `CharacterView::variants` order
+	pub fn compare(&self, roster: &Roster, index: usize) -> Vec<(Compared, Vec<Option<f64>>)> {
+		let variants = self.characters.get(index).map(|view| view.variants.as_slice()).unwrap_or_default();
+		Compared::all()
+			.map(|compared| {
+				let values = variants
+					.iter()
+					.map(|variant| {
+						let build = self.build(roster, *variant)?;
+						compared.value(build, self.calculation(roster, *variant))
+					})
+					.collect();
+				(compared, values)
+			})
+			.collect()
+	}
+}
+
+// What the variant comparison puts side by side
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compared {
+	CritValue,
+	Stat(StatKind),
+	Ranking,
+}
+
+impl Compared {
+	pub fn all() -> impl Iterator<Item = Compared> {
+		std::iter::once(Compared::CritValue)
+			.chain(stats::CORE_STATS.iter().map(|kind| Compared::Stat(*kind)))
+			.chain(std::iter::once(Compared::Ranking))
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Compared::CritValue => "Crit Value",
+			Compared::Stat(kind) => kind.name(),
+			Compared::Ranking => "Ranking",
+		}
+	}
+
+	fn value(self, build: &Value, calc: Option<&Value>) -> Option<f64> {
+		match self {
+			Compared::CritValue => build["critValue"].as_f64(),
+			Compared::Stat(kind) => stats::character_stat(build, kind),
+			Compared::Ranking => calc?["calculations"]["fit"]["ranking"].as_f64(),
+		}
+	}
+
+	// Positive when `value` is better than `current`, rankings improve going down
+	pub fn gain(self, value: f64, current: f64) -> f64 {
+		match self {
+			Compared::Ranking => current - value,
+			_ => value - current,
+		}
+	}
+}
+
+// What the calculation knows about a character without a build, with the weapon
+// the leaderboard ranked them with
+fn calculation_build(calc: &Value) -> Value {
+	let weapon = &calc["calculations"]["fit"]["weapon"];
+	let weapon = weapon.is_object().then(|| {
+		json!({
+			"name": weapon["name"],
+			"icon": weapon["icon"],
+			"weaponInfo": {
+				// The leaderboard counts refinements from 1, builds from 0
+				"refinementLevel": { "value": weapon["refinement"].as_i64().map(|refinement| refinement - 1) },
+			},
+		})
+	});
+	json!({
+		"type": models::build_type(calc),
+		"characterId": calc["characterId"],
+		"name": calc["name"],
+		"icon": calc["icon"],
+		"constellation": calc["constellation"],
+		"artifactSets": calc["artifactSets"],
+		"weapon": weapon,
+	})
+}
+
+fn ranking_text(calc: &Value) -> Option<String> {
+	let fit = &calc["calculations"]["fit"];
+	let (rank, total) = (fit["ranking"].as_i64()?, fit["outOf"].as_i64()?);
+	let percentage = (rank as f64 / total.max(1) as f64 * 100.0) as i64;
+	Some(format!("Top {}% ({}/{})", percentage, rank, total))
+}
+
+fn collect_icons(value: &Value, icons: &mut Vec<String>) {
+	match value {
+		Value::Object(map) => {
+			for (key, value) in map {
+				match value.as_str() {
+					Some(icon) if key == "icon" => icons.push(utils::icon_url(icon)),
+					_ => collect_icons(value, icons),
+				}
+			}
+		}
+		Value::Array(items) => {
+			for item in items {
+				collect_icons(item, icons);
+			}
+		}
+		_ => {}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::{fixture_roster, variant_roster};
+
+	#[test]
+	fn matches_the_roster_lookups() {
+		let roster = fixture_roster();
+		let view = RosterView::new(&roster);
+		assert_eq!(view.characters.len(), roster.characters.len());
+
+		for (index, char) in roster.characters.iter().enumerate() {
+			assert_eq!(
+				view.calculation(&roster, index).map(|calc| &calc["_id"]),
+				roster.calculation_for(char).map(|calc| &calc["_id"]),
+			);
+			assert!(view.characters[index].incomplete.is_none());
+		}
+		for sort in SortOrder::ALL {
+			assert_eq!(view.sorted(sort), roster.sorted_indices(sort).as_slice());
+		}
+		assert!(view.summary.best.is_some());
+		assert_ne!(RosterView::new(&roster).id, view.id);
+	}
+
+	#[test]
+	fn resolves_urls_and_text_up_front() {
+		let roster = fixture_roster();
+		let view = RosterView::new(&roster);
+		let index = |name: &str| roster.characters.iter().position(|c| c["name"] == name).unwrap();
+		let bennett = &view.characters[index("Bennett")];
+
+		assert_eq!(bennett.assets.name, "Bennett");
+		assert_eq!(bennett.element, "pyro");
+		assert_eq!(bennett.background_url, "https://akasha.cv/elementalBackgrounds/Pyro-bg.jpg");
+		assert_eq!(bennett.assets.splash_url, "https://enka.network/ui/UI_Gacha_AvatarImg_Bennett.png");
+		assert_eq!(bennett.icon_url.as_deref(), Some("https://enka.network/ui/UI_AvatarIcon_Bennett.png"));
+		assert!(bennett.ranking_text.is_none());
+		assert!(bennett.icons.contains(&"https://enka.network/ui/UI_EquipIcon_Sword_Dvalin_Awaken.png".to_string()));
+		assert_eq!(bennett.element_text(Theme::Light), theme::element_text_rgb("Pyro", Theme::Light));
+
+		let xiangling = &view.characters[index("Xiangling")];
+		assert_eq!(xiangling.assets.splash_url, "https://enka.network/ui/UI_Costume_XianglingCostumeWinter.png");
+		assert!(xiangling.ranking_text.as_deref().is_some_and(|text| text.starts_with("Top ")));
+
+		assert!(view.matches(index("Bennett"), "benn"));
+		assert!(!view.matches(index("Bennett"), "furina"));
+		assert!(!view.matches(99, ""));
+	}
+
+	#[test]
+	fn lists_characters_that_only_have_calculations() {
+		let mut roster = fixture_roster();
+		roster.characters.retain(|char| char["name"] != "Xiangling");
+		let view = RosterView::new(&roster);
+		assert_eq!(view.characters.len(), 15);
+		assert_eq!(view.characters.iter().filter(|char| char.calculation_only).count(), 1);
+		assert_eq!(view.summary.character_count, 15);
+
+		let xiangling = &view.characters[14];
+		assert_eq!(xiangling.name, "Xiangling");
+		assert_eq!(xiangling.incomplete.as_deref(), Some("Only on the leaderboard, full stats are unavailable"));
+		assert!(xiangling.ranking_text.is_some());
+		assert!(view.sorted(SortOrder::Name).contains(&14));
+
+		let build = view.build(&roster, 14).unwrap();
+		assert_eq!(build["weapon"]["name"], "\"The Catch\"");
+		assert_eq!(build["weapon"]["weaponInfo"]["refinementLevel"]["value"], 4);
+		assert_eq!(view.calculation(&roster, 14).unwrap()["name"], "Xiangling");
+		assert!(view.build(&roster, 15).is_none());
+	}
+
+	#[test]
+	fn groups_saved_builds_under_the_current_one() {
+		let roster = variant_roster("Furina", "Saved");
+		let view = RosterView::new(&roster);
+		assert_eq!(view.characters.len(), 16);
+
+		let (current, saved) = (&view.characters[0], &view.characters[15]);
+		assert_eq!((current.variant.as_str(), saved.variant.as_str()), ("current", "Saved"));
+		assert_eq!(current.variants, vec![0, 15]);
+		assert_eq!(saved.variants, current.variants);
+		assert_eq!((view.primary(15), view.primary(3)), (0, 3));
+		for sort in SortOrder::ALL {
+			assert_eq!(view.sorted(sort).len(), 15);
+			assert!(!view.sorted(sort).contains(&15));
+		}
+
+		// Each variant keeps its own leaderboard entry
+		let fit = |index| &view.calculation(&roster, index).unwrap()["calculations"]["fit"];
+		assert_eq!(fit(15)["ranking"].as_i64().unwrap(), fit(0)["ranking"].as_i64().unwrap() + 100);
+
+		let compared = view.compare(&roster, 15);
+		let (_, crit_values) = compared.iter().find(|(compared, _)| *compared == Compared::CritValue).unwrap();
+		assert_eq!(crit_values[1], Some(150.0));
+		let (_, rankings) = compared.iter().find(|(compared, _)| *compared == Compared::Ranking).unwrap();
+		assert!(Compared::Ranking.gain(rankings[1].unwrap(), rankings[0].unwrap()) < 0.0);
+		assert_eq!(compared.len(), stats::CORE_STATS.len() + 2);
+
+		// The summary only counts current builds
+		let fixture = RosterView::new(&fixture_roster());
+		assert_eq!(view.summary.character_count, 15);
+		assert_eq!(view.summary.average_crit_value, fixture.summary.average_crit_value);
+		let ranking = |summary: &RosterSummary| summary.worst.as_ref().map(|worst| worst.ranking);
+		assert_eq!(ranking(&view.summary), ranking(&fixture.summary));
+	}
+}
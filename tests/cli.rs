@@ -0,0 +1,160 @@
+// Runs the genshin-cli binary against the offline mock server
+#[allow(dead_code)]
+#[path = "../src/test_support/mock_server.rs"]
+mod mock_server;
+
+use mock_server::{MockServer, Reply, FIXTURE_UID};
+use std::process::{Command, Output};
+
+fn run_cli(server: &MockServer, args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_genshin-cli"))
+		.arg("--api-url")
+		.arg(server.api_url())
+		.args(args)
+		.env("NO_COLOR", "1")
+		.output()
+		.expect("run genshin-cli")
+}
+
+#[test]
+fn prints_every_build() {
+	let server = MockServer::start();
+	let output = run_cli(&server, &[FIXTURE_UID]);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	assert!(stdout.contains("Found 15 characters"));
+	assert!(stdout.contains("\nFurina (C"));
+	assert_eq!(stdout.matches("\nTalents:").count(), 15);
+	// Only characters with leaderboard data get an analysis
+	assert_eq!(stdout.matches("\nBuild Analysis:").count(), 7);
+	assert!(stdout.contains("Ranking: "));
+	assert_eq!(server.hits("/api/builds/"), 1);
+}
+
+#[test]
+fn invalid_uid_is_rejected_before_fetching() {
+	let server = MockServer::start();
+	let output = run_cli(&server, &["12345"]);
+
+	assert_eq!(output.status.code(), Some(2));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid UID"));
+	assert_eq!(server.hits("/api/"), 0);
+}
+
+#[test]
+fn server_errors_fail_the_command() {
+	let server = MockServer::start();
+	server.reply("/api/builds/", Reply::Status(500));
+	let output = run_cli(&server, &[FIXTURE_UID]);
+
+	assert_eq!(output.status.code(), Some(1));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Error fetching user data"));
+}
+
+#[test]
+fn failed_calculations_still_print_builds() {
+	let server = MockServer::start();
+	server.reply("/api/getCalculationsForUser/", Reply::Status(500));
+	let output = run_cli(&server, &[FIXTURE_UID]);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+
+	assert!(output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: Failed to fetch calculations"));
+	assert!(stdout.contains("Found 15 characters"));
+	assert_eq!(stdout.matches("\nBuild Analysis:").count(), 0);
+}
+
+#[test]
+fn empty_accounts_fail_the_command() {
+	let server = MockServer::start();
+	server.reply("/api/builds/", Reply::Json("{\"data\":[]}".to_string()));
+	let output = run_cli(&server, &[FIXTURE_UID]);
+
+	assert_eq!(output.status.code(), Some(1));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("No data found"));
+}
+
+#[test]
+fn lists_characters_with_only_calculations() {
+	let server = MockServer::start();
+	let mut builds: serde_json::Value = serde_json::from_str(mock_server::FIXTURE_BUILDS).unwrap();
+	builds["data"].as_array_mut().unwrap().retain(|char| char["name"] != "Xiangling");
+	server.reply("/api/builds/", Reply::Json(builds.to_string()));
+	let output = run_cli(&server, &[FIXTURE_UID]);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	assert!(stdout.contains("Found 15 characters"));
+	assert!(stdout.contains("\nXiangling (C1)\nOnly on the leaderboard, full stats are unavailable"));
+	assert_eq!(stdout.matches("\nTalents:").count(), 14);
+	assert_eq!(stdout.matches("\nBuild Analysis:").count(), 7);
+}
+
+#[test]
+fn prints_saved_builds_after_the_current_one() {
+	let server = MockServer::start();
+	let mut builds: serde_json::Value = serde_json::from_str(mock_server::FIXTURE_BUILDS).unwrap();
+	let mut saved = builds["data"][0].clone();
+	saved["_id"] = "saved-furina".into();
+	saved["type"] = "Hydro DPS".into();
+	builds["data"].as_array_mut().unwrap().push(saved);
+	server.reply("/api/builds/", Reply::Json(builds.to_string()));
+	let output = run_cli(&server, &[FIXTURE_UID]);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	assert!(stdout.contains("Found 15 characters"));
+	assert_eq!(stdout.matches("\nTalents:").count(), 16);
+	let saved = stdout.find("Saved build: Hydro DPS").unwrap();
+	assert!(stdout.find("\nFurina (C").unwrap() < saved);
+	assert!(saved < stdout.find("\nArlecchino (C").unwrap());
+}
+
+#[test]
+fn warns_when_not_every_build_was_read() {
+	let server = MockServer::start();
+	let builds: serde_json::Value = serde_json::from_str(mock_server::FIXTURE_BUILDS).unwrap();
+	// Every page comes back full, reading stops at the page limit
+	for page in 1..=11 {
+		let entries: Vec<serde_json::Value> = (0..20)
+			.map(|index| {
+				let mut build = builds["data"][0].clone();
+				build["_id"] = format!("saved-{}-{}", page, index).into();
+				build
+			})
+			.collect();
+		let url = format!("/api/builds/?sort=critValue&order=-1&size=20&page={}&", page);
+		server.reply(&url, Reply::Json(serde_json::json!({ "data": entries }).to_string()));
+	}
+	let output = run_cli(&server, &[FIXTURE_UID]);
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: Only the first 200 builds were read"));
+	assert_eq!(server.hits("/api/builds/"), 10);
+}
+
+#[test]
+fn prints_saved_json_without_fetching() {
+	let server = MockServer::start();
+	let root = env!("CARGO_MANIFEST_DIR");
+	let output = run_cli(
+		&server,
+		&[
+			"--from-file",
+			&format!("{}/example_build.json", root),
+			"--from-file",
+			&format!("{}/example_calculation.json", root),
+		],
+	);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	assert!(stdout.contains("Found 15 characters"));
+	assert_eq!(stdout.matches("\nBuild Analysis:").count(), 7);
+	assert_eq!(server.hits("/api/"), 0);
+
+	let output = run_cli(&server, &["--from-file", &format!("{}/Cargo.toml", root)]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("Cargo.toml"));
+}